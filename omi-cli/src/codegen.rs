@@ -0,0 +1,538 @@
+// Copyright (c) The Amphitheatre Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use omi::model::{Column, DataKind, Index, ReferentialAction, Table};
+
+/// Rust keywords, which can't be used as field or module names.
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn", "else", "enum",
+    "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+    "mut", "pub", "ref", "return", "self", "static", "struct", "super", "trait", "true", "try",
+    "type", "unsafe", "use", "where", "while", "yield",
+];
+
+/// An entity to generate, built from a table of the introspected schema.
+struct EntityDef<'a> {
+    table: &'a Table,
+    struct_name: String,
+    fields: Vec<FieldDef>,
+    /// Comments written at the end of the struct, for the foreign keys that
+    /// are not generated as relations.
+    notes: Vec<String>,
+}
+
+struct FieldDef {
+    attr: Option<String>,
+    name: String,
+    ty: String,
+}
+
+/// Generate the Rust source of the entity structs for the tables, all in a
+/// single file.
+pub fn generate(tables: &[Table]) -> String {
+    let mut source = String::from("use omi::prelude::*;\n");
+
+    for table in tables {
+        source.push('\n');
+        source.push_str(&render(&EntityDef::new(table, tables)));
+    }
+
+    source
+}
+
+/// Generate one module file per table, plus a `mod.rs` that declares the
+/// modules and re-exports the entities.
+pub fn generate_modules(tables: &[Table]) -> Vec<(String, String)> {
+    let mut files = vec![];
+    let mut mod_rs = String::new();
+
+    for table in tables {
+        let entity = EntityDef::new(table, tables);
+        let module = identifier(&singular(&table.name).to_snake_case(), "_");
+
+        let mut source = String::from("use omi::prelude::*;\n");
+        let mut related: Vec<String> = entity
+            .related()
+            .into_iter()
+            .filter(|r| *r != entity.struct_name)
+            .collect();
+        related.sort();
+        related.dedup();
+        match related.len() {
+            0 => {}
+            1 => source.push_str(&format!("\nuse super::{};\n", related[0])),
+            _ => source.push_str(&format!("\nuse super::{{{}}};\n", related.join(", "))),
+        }
+        source.push('\n');
+        source.push_str(&render(&entity));
+
+        mod_rs.push_str(&format!(
+            "mod {};\npub use self::{}::{};\n\n",
+            module, module, entity.struct_name
+        ));
+        files.push((format!("{}.rs", module), source));
+    }
+
+    files.push((String::from("mod.rs"), format!("{}\n", mod_rs.trim_end())));
+    files
+}
+
+impl<'a> EntityDef<'a> {
+    fn new(table: &'a Table, tables: &[Table]) -> Self {
+        let struct_name = entity_name(&table.name);
+        let prefix = singular(&table.name).to_snake_case();
+
+        // Plain single column indexes are written as column attributes.
+        let mut fields: Vec<FieldDef> = table
+            .columns
+            .iter()
+            .map(|column| {
+                let mut column = column.clone();
+                for index in table.indexes.iter().filter(|i| is_column_index(i)) {
+                    if index.columns[0] == column.name {
+                        column.unique |= index.unique;
+                        column.index |= !index.unique;
+                    }
+                }
+                column_field(&column, &prefix)
+            })
+            .collect();
+
+        let mut notes = vec![];
+        for fk in &table.foreign_keys {
+            // Self references would make the struct infinitely sized.
+            if fk.referenced_table == table.name
+                || !tables.iter().any(|t| t.name == fk.referenced_table)
+            {
+                continue;
+            }
+            // A relation field can only express a single column key.
+            if fk.columns.len() != 1 {
+                notes.push(format!(
+                    "// composite foreign key `{}` ({}) references {} ({}), not generated as a relation",
+                    fk.name,
+                    fk.columns.join(", "),
+                    fk.referenced_table,
+                    fk.referenced_columns.join(", ")
+                ));
+                continue;
+            }
+            let (column_name, referenced_column) = (&fk.columns[0], &fk.referenced_columns[0]);
+
+            let mut name = match column_name.strip_suffix("_id") {
+                Some(stem) if !stem.is_empty() => stem.to_snake_case(),
+                _ => singular(&fk.referenced_table).to_snake_case(),
+            };
+            if fields.iter().any(|f| f.name == name) {
+                name = format!("{}_relation", name);
+            }
+
+            let target = entity_name(&fk.referenced_table);
+
+            let mut args = vec![
+                String::from("belongs_to"),
+                format!("local_key = \"{}\"", column_name),
+                format!("foreign_key = \"{}\"", referenced_column),
+            ];
+            for (key, action) in [("on_delete", fk.on_delete), ("on_update", fk.on_update)] {
+                if action != ReferentialAction::NoAction {
+                    args.push(format!("{} = \"{}\"", key, action_name(action)));
+                }
+            }
+
+            // Always optional, as the related entity is only set when it's
+            // loaded, and two tables referencing each other would otherwise
+            // make both structs infinitely sized.
+            fields.push(FieldDef {
+                attr: Some(format!("#[relation({})]", args.join(", "))),
+                name: identifier(&name, "_relation"),
+                ty: format!("Option<{}>", target),
+            });
+        }
+
+        Self {
+            table,
+            struct_name,
+            fields,
+            notes,
+        }
+    }
+
+    /// The names of the entities referenced by relation fields.
+    fn related(&self) -> Vec<String> {
+        self.fields
+            .iter()
+            .filter(|f| matches!(f.attr.as_deref(), Some(a) if a.starts_with("#[relation")))
+            .map(|f| {
+                f.ty.trim_start_matches("Option<")
+                    .trim_end_matches('>')
+                    .to_owned()
+            })
+            .collect()
+    }
+}
+
+fn render(entity: &EntityDef) -> String {
+    let mut source = String::new();
+
+    source.push_str(
+        "#[derive(Debug, Default, Clone, PartialEq, Entity, Queryable, Creatable, Updatable, Deletable)]\n",
+    );
+    source.push_str(&format!(
+        "#[entity({})]\n",
+        entity_args(entity.table).join(", ")
+    ));
+    source.push_str(&format!("pub struct {} {{\n", entity.struct_name));
+
+    for (i, field) in entity.fields.iter().enumerate() {
+        if i > 0 {
+            source.push('\n');
+        }
+        if let Some(attr) = &field.attr {
+            source.push_str(&format!("    {}\n", attr));
+        }
+        source.push_str(&format!("    pub {}: {},\n", field.name, field.ty));
+    }
+    if !entity.notes.is_empty() {
+        source.push('\n');
+    }
+    for note in &entity.notes {
+        source.push_str(&format!("    {}\n", note));
+    }
+
+    source.push_str("}\n");
+    source
+}
+
+fn entity_args(table: &Table) -> Vec<String> {
+    let mut args = vec![format!("table = \"{}\"", table.name)];
+
+    for index in table.indexes.iter().filter(|i| !is_column_index(i)) {
+        let columns: Vec<String> = index.columns.iter().map(|c| format!("{:?}", c)).collect();
+        let mut index_args = vec![format!("columns({})", columns.join(", "))];
+        if index.unique {
+            index_args.push(String::from("unique"));
+        }
+        index_args.push(format!("name = {:?}", index.name));
+        if let Some(condition) = &index.condition {
+            index_args.push(format!("where = {:?}", condition));
+        }
+        args.push(format!("index({})", index_args.join(", ")));
+    }
+
+    for check in &table.checks {
+        args.push(format!(
+            "check(expr = {:?}, name = {:?})",
+            check.expression, check.name
+        ));
+    }
+
+    args
+}
+
+fn is_column_index(index: &Index) -> bool {
+    index.columns.len() == 1 && index.condition.is_none()
+}
+
+fn column_field(column: &Column, prefix: &str) -> FieldDef {
+    let mut name = column.name.to_snake_case();
+    if KEYWORDS.contains(&name.as_str()) || name.is_empty() {
+        name = format!("{}_{}", prefix, name);
+    }
+
+    let mut args = vec![];
+    if column.primary {
+        args.push(String::from("primary"));
+    }
+    if column.auto {
+        args.push(String::from("auto"));
+    }
+    if name != column.name {
+        args.push(format!("rename = {:?}", column.name));
+    }
+
+    let (ty, column_type) = match column.kind {
+        DataKind::Integer(_) if column.auto => ("u64", None),
+        DataKind::Integer(_) => ("i64", None),
+        DataKind::Float { .. } => ("f64", None),
+        DataKind::Text(_) => ("String", None),
+        DataKind::Blob => ("Vec<u8>", Some("blob")),
+        DataKind::Timestamp => ("String", Some("timestamp")),
+        DataKind::Date => ("String", Some("date")),
+        DataKind::Time => ("String", Some("time")),
+        DataKind::Datetime => ("String", Some("datetime")),
+        DataKind::Year => ("i32", Some("year")),
+        DataKind::Boolean => ("bool", None),
+    };
+    if let Some(column_type) = column_type {
+        args.push(format!("type = \"{}\"", column_type));
+    }
+
+    // Integers without a size are BIGINT already.
+    match column.kind {
+        DataKind::Integer(Some(size)) if size < 19 => args.push(format!("size = {}", size)),
+        DataKind::Text(Some(size)) => args.push(format!("size = {}", size)),
+        _ => {}
+    }
+    if let Some(default) = &column.default {
+        args.push(format!("default = {:?}", default));
+    }
+    if column.unique {
+        args.push(String::from("unique"));
+    }
+    if column.index {
+        args.push(String::from("index"));
+    }
+    if let DataKind::Float {
+        max_digits,
+        decimal_places,
+    } = column.kind
+    {
+        let attrs: Vec<String> = [
+            ("max_digits", max_digits),
+            ("decimal_places", decimal_places),
+        ]
+        .iter()
+        .filter_map(|(key, value)| value.map(|v| format!("{} = {}", key, v)))
+        .collect();
+        if !attrs.is_empty() {
+            args.push(format!("attrs({})", attrs.join(", ")));
+        }
+    }
+
+    FieldDef {
+        attr: match args.is_empty() {
+            true => None,
+            false => Some(format!("#[column({})]", args.join(", "))),
+        },
+        name,
+        ty: match column.null {
+            true => format!("Option<{}>", ty),
+            false => ty.to_owned(),
+        },
+    }
+}
+
+/// The attribute value of the action, e.g. `SET NULL` becomes `set_null`.
+fn action_name(action: ReferentialAction) -> String {
+    action.as_sql().to_ascii_lowercase().replace(' ', "_")
+}
+
+/// The struct name of the table, e.g. `product_reviews` becomes `ProductReview`.
+fn entity_name(table: &str) -> String {
+    singular(table).to_upper_camel_case()
+}
+
+/// A naive singular form of the table name, which covers the usual English
+/// plurals used for table names.
+fn singular(name: &str) -> String {
+    if let Some(stem) = name.strip_suffix("ies") {
+        format!("{}y", stem)
+    } else if ["sses", "shes", "ches", "xes", "zes"]
+        .iter()
+        .any(|s| name.ends_with(s))
+    {
+        name[..name.len() - 2].to_owned()
+    } else if name.ends_with('s') && !name.ends_with("ss") && !name.ends_with("us") {
+        name[..name.len() - 1].to_owned()
+    } else {
+        name.to_owned()
+    }
+}
+
+fn identifier(name: &str, suffix: &str) -> String {
+    match KEYWORDS.contains(&name) {
+        true => format!("{}{}", name, suffix),
+        false => name.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use omi::model::{Check, Column, DataKind, ForeignKey, Index, ReferentialAction, Table};
+
+    use super::{generate, generate_modules, singular};
+
+    fn column(name: &str, kind: DataKind, null: bool) -> Column {
+        Column {
+            name: name.into(),
+            kind,
+            null,
+            primary: false,
+            default: None,
+            auto: false,
+            unique: false,
+            index: false,
+        }
+    }
+
+    fn tables() -> Vec<Table> {
+        let mut id = column("id", DataKind::Integer(Some(19)), false);
+        id.primary = true;
+        id.auto = true;
+        let mut title = column("title", DataKind::Text(Some(255)), false);
+        title.default = Some("".into());
+
+        vec![
+            Table {
+                name: "brands".into(),
+                columns: vec![id.clone()],
+                ..Default::default()
+            },
+            Table {
+                name: "products".into(),
+                columns: vec![
+                    id,
+                    title,
+                    column(
+                        "price",
+                        DataKind::Float {
+                            max_digits: Some(8),
+                            decimal_places: Some(2),
+                        },
+                        false,
+                    ),
+                    column("type", DataKind::Text(None), false),
+                    column("deleted_at", DataKind::Timestamp, true),
+                    column("brand_id", DataKind::Integer(Some(10)), true),
+                ],
+                foreign_keys: vec![ForeignKey {
+                    name: "fk_products_brand_id".into(),
+                    columns: vec!["brand_id".into()],
+                    referenced_table: "brands".into(),
+                    referenced_columns: vec!["id".into()],
+                    on_delete: ReferentialAction::SetNull,
+                    on_update: ReferentialAction::NoAction,
+                }],
+                indexes: vec![
+                    Index {
+                        name: "idx_products_title".into(),
+                        columns: vec!["title".into()],
+                        unique: false,
+                        condition: None,
+                    },
+                    Index {
+                        name: "idx_products_brand_title".into(),
+                        columns: vec!["brand_id".into(), "title".into()],
+                        unique: true,
+                        condition: Some("deleted_at IS NULL".into()),
+                    },
+                ],
+                checks: vec![Check {
+                    name: "chk_products_price".into(),
+                    expression: "price >= 0".into(),
+                }],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_generate_entity() {
+        let source = generate(&tables());
+
+        assert!(source.contains(
+            "#[derive(Debug, Default, Clone, PartialEq, Entity, Queryable, Creatable, Updatable, Deletable)]\n\
+             #[entity(table = \"products\", index(columns(\"brand_id\", \"title\"), unique, name = \
+             \"idx_products_brand_title\", where = \"deleted_at IS NULL\"), check(expr = \"price >= 0\", \
+             name = \"chk_products_price\"))]\npub struct Product {\n    #[column(primary, auto)]\n    pub id: u64,\n"
+        ));
+        assert!(source.contains(
+            "    #[column(size = 255, default = \"\", index)]\n    pub title: String,\n"
+        ));
+        assert!(source.contains(
+            "    #[column(attrs(max_digits = 8, decimal_places = 2))]\n    pub price: f64,\n"
+        ));
+        assert!(
+            source.contains("    #[column(rename = \"type\")]\n    pub product_type: String,\n")
+        );
+        assert!(source.contains(
+            "    #[column(type = \"timestamp\")]\n    pub deleted_at: Option<String>,\n"
+        ));
+        assert!(source.contains("    #[column(size = 10)]\n    pub brand_id: Option<i64>,\n"));
+        assert!(source.contains(
+            "    #[relation(belongs_to, local_key = \"brand_id\", foreign_key = \"id\", on_delete = \
+             \"set_null\")]\n    pub brand: Option<Brand>,\n"
+        ));
+    }
+
+    #[test]
+    fn test_generate_modules() {
+        let files = generate_modules(&tables());
+        let names: Vec<&str> = files.iter().map(|(name, _)| name.as_str()).collect();
+
+        assert_eq!(names, vec!["brand.rs", "product.rs", "mod.rs"]);
+        assert!(files[1]
+            .1
+            .starts_with("use omi::prelude::*;\n\nuse super::Brand;\n"));
+        assert_eq!(
+            files[2].1,
+            "mod brand;\npub use self::brand::Brand;\n\nmod product;\npub use self::product::Product;\n"
+        );
+    }
+
+    #[test]
+    fn test_generate_cycle() {
+        let table = |name: &str, foreign: &str| Table {
+            name: name.into(),
+            columns: vec![column(&format!("{}_id", foreign), DataKind::Integer(None), false)],
+            foreign_keys: vec![ForeignKey {
+                name: format!("fk_{}_{}", name, foreign),
+                columns: vec![format!("{}_id", foreign)],
+                referenced_table: format!("{}s", foreign),
+                referenced_columns: vec!["id".into()],
+                on_delete: ReferentialAction::NoAction,
+                on_update: ReferentialAction::NoAction,
+            }],
+            ..Default::default()
+        };
+        let source = generate(&[table("users", "team"), table("teams", "user")]);
+
+        assert!(source.contains(
+            "    #[relation(belongs_to, local_key = \"team_id\", foreign_key = \"id\")]\n    pub team: Option<Team>,\n"
+        ));
+        assert!(source.contains(
+            "    #[relation(belongs_to, local_key = \"user_id\", foreign_key = \"id\")]\n    pub user: Option<User>,\n"
+        ));
+    }
+
+    #[test]
+    fn test_generate_composite_foreign_key() {
+        let mut tables = tables();
+        tables[1].foreign_keys = vec![ForeignKey {
+            name: "fk_products_brand".into(),
+            columns: vec!["brand_id".into(), "title".into()],
+            referenced_table: "brands".into(),
+            referenced_columns: vec!["id".into(), "name".into()],
+            on_delete: ReferentialAction::NoAction,
+            on_update: ReferentialAction::NoAction,
+        }];
+        let source = generate(&tables);
+
+        assert!(!source.contains("#[relation("));
+        assert!(source.contains(
+            "    pub brand_id: Option<i64>,\n\n    // composite foreign key `fk_products_brand` (brand_id, title) \
+             references brands (id, name), not generated as a relation\n}\n"
+        ));
+    }
+
+    #[test]
+    fn test_singular() {
+        assert_eq!(singular("categories"), "category");
+        assert_eq!(singular("boxes"), "box");
+        assert_eq!(singular("products"), "product");
+        assert_eq!(singular("status"), "status");
+        assert_eq!(singular("address"), "address");
+    }
+}
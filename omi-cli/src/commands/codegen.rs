@@ -0,0 +1,61 @@
+// Copyright (c) The Amphitheatre Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::path::PathBuf;
+
+use clap::Args;
+
+use super::Context;
+use crate::{codegen, Result};
+
+#[derive(Debug, Args)]
+pub struct CodegenArgs {
+    /// Only generate entities for these tables
+    #[arg(long, value_delimiter = ',')]
+    tables: Vec<String>,
+
+    /// Write the generated source to this file instead of stdout
+    #[arg(long, short, conflicts_with = "out_dir")]
+    out: Option<PathBuf>,
+
+    /// Write one module per table, plus a `mod.rs`, into this directory
+    #[arg(long)]
+    out_dir: Option<PathBuf>,
+}
+
+pub fn run(ctx: &Context, args: &CodegenArgs) -> Result<()> {
+    let db = ctx.connect()?;
+    let tables: Vec<_> = ctx
+        .live_tables(&db)?
+        .into_iter()
+        .filter(|t| args.tables.is_empty() || args.tables.contains(&t.name))
+        .collect();
+
+    if let Some(dir) = &args.out_dir {
+        fs::create_dir_all(dir)?;
+        for (file, source) in codegen::generate_modules(&tables) {
+            fs::write(dir.join(file), source)?;
+        }
+        return Ok(());
+    }
+
+    let source = codegen::generate(&tables);
+    match &args.out {
+        Some(path) => fs::write(path, source)?,
+        None => print!("{}", source),
+    }
+
+    Ok(())
+}
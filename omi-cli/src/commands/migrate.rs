@@ -0,0 +1,152 @@
+// Copyright (c) The Amphitheatre Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::slice;
+
+use clap::Subcommand;
+use omi::schema;
+
+use super::Context;
+use crate::migration::{self, Migration};
+use crate::Result;
+
+#[derive(Debug, Subcommand)]
+pub enum MigrateCommand {
+    /// Apply the pending migrations
+    Up {
+        /// Apply at most this many migrations
+        #[arg(long)]
+        steps: Option<usize>,
+    },
+    /// Revert the latest applied migrations
+    Down {
+        /// Revert this many migrations
+        #[arg(long, default_value_t = 1)]
+        steps: usize,
+    },
+    /// Show the applied and pending migrations
+    Status,
+    /// Generate a new migration, filled with the diff between the live
+    /// schema and the entities when they are known to the tool
+    Generate {
+        /// The name of the migration, e.g. `create_products`
+        name: String,
+    },
+}
+
+pub fn run(ctx: &Context, command: &MigrateCommand) -> Result<()> {
+    let dir = &ctx.config.migrations.dir;
+
+    match command {
+        MigrateCommand::Up { steps } => {
+            let db = ctx.connect()?;
+            migration::prepare(&db)?;
+
+            let applied = migration::applied(&db)?;
+            let pending = migration::discover(dir)?
+                .into_iter()
+                .filter(|m| !applied.contains(&m.version))
+                .take(steps.unwrap_or(usize::MAX));
+
+            for m in pending {
+                migration::apply(&db, &m)?;
+                println!("Applied {}_{}", m.version, m.name);
+            }
+        }
+        MigrateCommand::Down { steps } => {
+            let db = ctx.connect()?;
+            migration::prepare(&db)?;
+
+            let applied = migration::applied(&db)?;
+            let migrations: Vec<Migration> = migration::discover(dir)?
+                .into_iter()
+                .filter(|m| applied.contains(&m.version))
+                .rev()
+                .take(*steps)
+                .collect();
+
+            for m in migrations {
+                migration::revert(&db, &m)?;
+                println!("Reverted {}_{}", m.version, m.name);
+            }
+        }
+        MigrateCommand::Status => {
+            let db = ctx.connect()?;
+            migration::prepare(&db)?;
+
+            let applied = migration::applied(&db)?;
+            for m in migration::discover(dir)? {
+                let state = match applied.contains(&m.version) {
+                    true => "applied",
+                    false => "pending",
+                };
+                println!("{:<8} {}_{}", state, m.version, m.name);
+            }
+        }
+        MigrateCommand::Generate { name } => {
+            let (up, down) = match ctx.tables.is_empty() {
+                true => (
+                    String::from("-- Write the SQL that applies the migration here\n"),
+                    String::from("-- Write the SQL that reverts the migration here\n"),
+                ),
+                false => diff_scripts(ctx)?,
+            };
+
+            let m = migration::scaffold(dir, name, &up, &down)?;
+            println!("Generated {}", m.path.display());
+        }
+    }
+
+    Ok(())
+}
+
+/// Generate the up and down scripts from the diff between the live schema
+/// and the tables of the entities.
+fn diff_scripts(ctx: &Context) -> Result<(String, String)> {
+    let db = ctx.connect()?;
+    let dialect = db.dialect()?;
+    let current = ctx.live_tables(&db)?;
+
+    let up = schema::diff(&current, &ctx.tables, dialect);
+
+    let mut down = schema::Changes::default();
+    for table in ctx.tables.iter().rev() {
+        match current.iter().find(|t| t.name == table.name) {
+            Some(existing) => down.extend(schema::diff(
+                slice::from_ref(table),
+                slice::from_ref(existing),
+                dialect,
+            )),
+            None => down
+                .statements
+                .push(schema::drop_table(&table.name, dialect)),
+        }
+    }
+
+    Ok((to_script(up), to_script(down)))
+}
+
+/// Join the statements into a script, the changes the dialect can't make are
+/// left out of it and reported.
+fn to_script(changes: schema::Changes) -> String {
+    for warning in &changes.warnings {
+        eprintln!("warning: {}", warning);
+    }
+
+    changes
+        .statements
+        .into_iter()
+        .map(|sql| format!("{};\n", sql))
+        .collect()
+}
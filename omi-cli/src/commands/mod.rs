@@ -0,0 +1,58 @@
+// Copyright (c) The Amphitheatre Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub mod codegen;
+pub use self::codegen::CodegenArgs;
+
+pub mod db;
+pub use self::db::DbCommand;
+
+pub mod migrate;
+pub use self::migrate::MigrateCommand;
+
+pub mod schema;
+use omi::model::Table;
+use omi::Database;
+
+pub use self::schema::SchemaCommand;
+use crate::migration::MIGRATIONS_TABLE;
+use crate::{Config, Result};
+
+/// The state shared by the commands.
+pub struct Context {
+    pub config: Config,
+    pub database_url: Option<String>,
+    /// The tables of the entities known to the application, which are the
+    /// target schema of `migrate generate`.
+    pub tables: Vec<Table>,
+}
+
+impl Context {
+    pub fn url(&self) -> Result<String> {
+        self.config.database_url(self.database_url.as_deref())
+    }
+
+    pub fn connect(&self) -> Result<Database> {
+        Ok(Database::connect(self.url()?))
+    }
+
+    /// Read the live tables, leaving out the migrations table.
+    pub fn live_tables(&self, db: &Database) -> Result<Vec<Table>> {
+        let tables = omi::schema::tables(db)?;
+        Ok(tables
+            .into_iter()
+            .filter(|t| t.name != MIGRATIONS_TABLE)
+            .collect())
+    }
+}
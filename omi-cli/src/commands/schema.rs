@@ -0,0 +1,60 @@
+// Copyright (c) The Amphitheatre Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::path::PathBuf;
+
+use clap::Subcommand;
+use omi::schema;
+
+use super::Context;
+use crate::Result;
+
+#[derive(Debug, Subcommand)]
+pub enum SchemaCommand {
+    /// Print the DDL of the live database
+    Dump {
+        /// Write the DDL to this file instead of stdout
+        #[arg(long, short)]
+        out: Option<PathBuf>,
+    },
+}
+
+pub fn run(ctx: &Context, command: &SchemaCommand) -> Result<()> {
+    match command {
+        SchemaCommand::Dump { out } => {
+            let db = ctx.connect()?;
+            let dialect = db.dialect()?;
+
+            let mut ddl = String::new();
+            for table in ctx.live_tables(&db)? {
+                let changes = schema::create_table(&table, dialect);
+                for warning in changes.warnings {
+                    eprintln!("warning: {}", warning);
+                }
+                for sql in changes.statements {
+                    ddl.push_str(&sql);
+                    ddl.push_str(";\n\n");
+                }
+            }
+
+            match out {
+                Some(path) => fs::write(path, ddl)?,
+                None => print!("{}", ddl),
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,371 @@
+// Copyright (c) The Amphitheatre Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use omi::model::format_utc;
+use omi::prelude::*;
+use omi::{schema, Database};
+
+use crate::{Error, Result};
+
+/// The table that records the applied migrations.
+pub const MIGRATIONS_TABLE: &str = "omi_migrations";
+
+#[derive(Debug, Default, Clone, Entity)]
+#[entity(table = "omi_migrations")]
+struct SchemaMigration {
+    #[column(primary, size = 32)]
+    version: String,
+}
+
+/// A migration directory, named `<version>_<name>` and holding an `up.sql`
+/// and a `down.sql` script.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Migration {
+    pub version: String,
+    pub name: String,
+    pub path: PathBuf,
+}
+
+impl Migration {
+    pub fn up_sql(&self) -> Result<String> {
+        self.read("up.sql")
+    }
+
+    pub fn down_sql(&self) -> Result<String> {
+        self.read("down.sql")
+    }
+
+    fn read(&self, file: &str) -> Result<String> {
+        let path = self.path.join(file);
+        fs::read_to_string(&path)
+            .map_err(|e| Error::Migration(format!("{}: {}", path.display(), e)))
+    }
+}
+
+/// Find the migrations in the directory, ordered by version.
+pub fn discover(dir: &Path) -> Result<Vec<Migration>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut migrations = vec![];
+    for entry in entries {
+        let path = entry?.path();
+        if !path.is_dir() {
+            continue;
+        }
+
+        let file_name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let (version, name) = file_name
+            .split_once('_')
+            .filter(|(version, _)| {
+                !version.is_empty() && version.chars().all(|c| c.is_ascii_digit())
+            })
+            .ok_or_else(|| {
+                Error::Migration(format!("invalid migration directory name: {}", file_name))
+            })?;
+
+        migrations.push(Migration {
+            version: version.to_owned(),
+            name: name.to_owned(),
+            path: path.clone(),
+        });
+    }
+
+    migrations.sort_by(|a, b| version_key(&a.version).cmp(&version_key(&b.version)));
+    for pair in migrations.windows(2) {
+        if version_key(&pair[0].version) == version_key(&pair[1].version) {
+            return Err(Error::Migration(format!(
+                "duplicate migration version: {}",
+                pair[1].version
+            )));
+        }
+    }
+
+    Ok(migrations)
+}
+
+/// The key that orders versions by their numeric value, whatever their
+/// width, without parsing them into a fixed size integer.
+fn version_key(version: &str) -> (usize, &str) {
+    let digits = version.trim_start_matches('0');
+    (digits.len(), digits)
+}
+
+/// Create the migrations table if it does not exist yet.
+pub fn prepare(db: &Database) -> Result<()> {
+    if schema::table(db, MIGRATIONS_TABLE)?.is_none() {
+        for sql in schema::create_table(&SchemaMigration::meta().table, db.dialect()?).statements {
+            db.execute::<()>(sql)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The versions of the applied migrations, ordered by version.
+pub fn applied(db: &Database) -> Result<Vec<String>> {
+    let sql = format!("SELECT version FROM {} ORDER BY version", MIGRATIONS_TABLE);
+    let rows = omi::raw::<SchemaMigration>(sql).execute(db)?;
+
+    Ok(rows.into_iter().map(|row| row.version).collect())
+}
+
+/// Run the `up.sql` script of the migration and record it as applied, in one
+/// transaction. Backends that commit DDL implicitly, such as MySQL, can't roll
+/// back the statements that already ran.
+pub fn apply(db: &Database, migration: &Migration) -> Result<()> {
+    let record = format!(
+        "INSERT INTO {} (version) VALUES ('{}')",
+        MIGRATIONS_TABLE, migration.version
+    );
+
+    run(db, split_statements(&migration.up_sql()?), record)
+}
+
+/// Run the `down.sql` script of the migration and remove its record, in one
+/// transaction.
+pub fn revert(db: &Database, migration: &Migration) -> Result<()> {
+    let record = format!(
+        "DELETE FROM {} WHERE version = '{}'",
+        MIGRATIONS_TABLE, migration.version
+    );
+
+    run(db, split_statements(&migration.down_sql()?), record)
+}
+
+fn run(db: &Database, statements: Vec<String>, record: String) -> Result<()> {
+    db.transaction(|tx| {
+        for sql in &statements {
+            tx.execute::<()>(sql.clone())?;
+        }
+        tx.execute::<()>(record.clone())?;
+
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+/// Create a new migration directory with the given scripts.
+pub fn scaffold(dir: &Path, name: &str, up: &str, down: &str) -> Result<Migration> {
+    let version = version_now();
+    let migration = Migration {
+        path: dir.join(format!("{}_{}", version, name)),
+        version,
+        name: name.to_owned(),
+    };
+
+    fs::create_dir_all(&migration.path)?;
+    fs::write(migration.path.join("up.sql"), up)?;
+    fs::write(migration.path.join("down.sql"), down)?;
+
+    Ok(migration)
+}
+
+/// Split a script into statements on `;`, ignoring the ones in string
+/// literals, which may escape quotes with a backslash, quoted identifiers,
+/// Postgres `$tag$` bodies and comments. `--` comments are left out of the
+/// statements.
+pub fn split_statements(script: &str) -> Vec<String> {
+    let chars: Vec<char> = script.chars().collect();
+    let mut statements = vec![];
+    let mut current = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let end = match (chars[i], chars.get(i + 1)) {
+            ('\'' | '"', _) => quoted(&chars, i + 1, chars[i]),
+            ('`', _) => find(&chars, i + 1, &['`']),
+            ('/', Some('*')) => find(&chars, i + 2, &['*', '/']),
+            ('$', _) => match dollar_tag(&chars[i..]) {
+                Some(tag) => find(&chars, i + tag.len(), &tag),
+                None => i + 1,
+            },
+            ('-', Some('-')) => {
+                // The comment runs to the end of the line, which is kept.
+                i = (i..chars.len())
+                    .find(|&j| chars[j] == '\n')
+                    .unwrap_or(chars.len());
+                continue;
+            }
+            (';', _) => {
+                statements.push(current.trim().to_owned());
+                current.clear();
+                i += 1;
+                continue;
+            }
+            _ => i + 1,
+        };
+
+        current.extend(&chars[i..end]);
+        i = end;
+    }
+    statements.push(current.trim().to_owned());
+
+    statements.into_iter().filter(|s| !s.is_empty()).collect()
+}
+
+/// The position after the closing delimiter found from `start`, or the end of
+/// the script if it's never closed.
+fn find(chars: &[char], start: usize, delimiter: &[char]) -> usize {
+    (start..chars.len())
+        .find(|&i| chars[i..].starts_with(delimiter))
+        .map_or(chars.len(), |i| i + delimiter.len())
+}
+
+/// The position after the closing quote of a string literal from `start`,
+/// skipping the quotes escaped with a backslash as MySQL does.
+fn quoted(chars: &[char], start: usize, quote: char) -> usize {
+    let mut i = start;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            c if c == quote => return i + 1,
+            _ => i += 1,
+        }
+    }
+
+    chars.len()
+}
+
+/// The `$tag$` that opens a dollar quoted body, the tag may be empty but
+/// can't start with a digit, so `$1` parameters are not mistaken for one.
+fn dollar_tag(chars: &[char]) -> Option<Vec<char>> {
+    let len = chars[1..]
+        .iter()
+        .position(|c| !(c.is_alphanumeric() || *c == '_'))?;
+    let tag = &chars[..len + 2];
+
+    match (tag[len + 1], tag.get(1)) {
+        ('$', Some(c)) if !c.is_ascii_digit() => Some(tag.to_vec()),
+        _ => None,
+    }
+}
+
+/// The current UTC time formatted as `YYYYMMDDHHMMSS`.
+fn version_now() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+
+    format_utc(secs).replace(['-', ' ', ':'], "")
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::{discover, split_statements, version_now};
+
+    #[test]
+    fn test_split_statements() {
+        let script = "CREATE TABLE a (x TEXT DEFAULT ';');\n-- comment; here\nDROP TABLE b;\n";
+
+        assert_eq!(
+            split_statements(script),
+            vec!["CREATE TABLE a (x TEXT DEFAULT ';')", "DROP TABLE b"]
+        );
+    }
+
+    #[test]
+    fn test_split_statements_trailing_comment() {
+        assert_eq!(
+            split_statements("DROP TABLE a;\n-- the end"),
+            vec!["DROP TABLE a"]
+        );
+        assert_eq!(split_statements("DROP TABLE a -- x"), vec!["DROP TABLE a"]);
+    }
+
+    #[test]
+    fn test_split_statements_escaped_quote() {
+        let script = "INSERT INTO a VALUES ('it\\'s; x', \"say \\\"hi\\\"; y\");\nDROP TABLE b;";
+
+        assert_eq!(
+            split_statements(script),
+            vec![
+                "INSERT INTO a VALUES ('it\\'s; x', \"say \\\"hi\\\"; y\")",
+                "DROP TABLE b"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_statements_quoted() {
+        let script = "CREATE TABLE \"a;b\" (x INT); /* one; two */\n\
+            CREATE FUNCTION f() RETURNS INT AS $$ BEGIN RETURN 1; END; $$ LANGUAGE plpgsql;\n\
+            CREATE FUNCTION g() RETURNS INT AS $body$ SELECT $1; $body$ LANGUAGE sql;";
+
+        assert_eq!(
+            split_statements(script),
+            vec![
+                "CREATE TABLE \"a;b\" (x INT)",
+                "/* one; two */\nCREATE FUNCTION f() RETURNS INT AS $$ BEGIN RETURN 1; END; $$ LANGUAGE plpgsql",
+                "CREATE FUNCTION g() RETURNS INT AS $body$ SELECT $1; $body$ LANGUAGE sql",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_discover_migrations() {
+        let dir = std::env::temp_dir().join(format!("omi-migrations-{}", std::process::id()));
+        fs::create_dir_all(dir.join("20230102000000_add_reviews")).unwrap();
+        fs::create_dir_all(dir.join("20230101000000_create_products")).unwrap();
+
+        let migrations = discover(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(migrations[0].name, "create_products");
+        assert_eq!(migrations[1].version, "20230102000000");
+    }
+
+    #[test]
+    fn test_discover_numeric_order() {
+        let dir = std::env::temp_dir().join(format!("omi-migrations-order-{}", std::process::id()));
+        fs::create_dir_all(dir.join("10_add_reviews")).unwrap();
+        fs::create_dir_all(dir.join("9_create_products")).unwrap();
+
+        let versions: Vec<String> = discover(&dir)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.version)
+            .collect();
+
+        fs::create_dir_all(dir.join("010_duplicate")).unwrap();
+        let duplicate = discover(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(versions, vec!["9", "10"]);
+        assert!(duplicate.is_err());
+    }
+
+    #[test]
+    fn test_version_now() {
+        let version = version_now();
+
+        assert_eq!(version.len(), 14);
+        assert!(version.starts_with("20"));
+    }
+}
@@ -8,6 +8,18 @@ pub fn impl_deletable(input: syn::DeriveInput) -> TokenStream {
             fn delete(entity: #ident) -> omi::statement::DeleteStatement<#ident> {
                 omi::statement::DeleteStatement::new(entity)
             }
+
+            fn force_delete(entity: #ident) -> omi::statement::DeleteStatement<#ident> {
+                omi::statement::DeleteStatement::force(entity)
+            }
+
+            fn restore(entity: #ident) -> omi::statement::DeleteStatement<#ident> {
+                omi::statement::DeleteStatement::restore(entity)
+            }
+
+            fn delete_all() -> omi::statement::DeleteStatement<#ident> {
+                omi::statement::DeleteStatement::bulk()
+            }
         }
     }
 }
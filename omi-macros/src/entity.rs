@@ -3,7 +3,7 @@ use heck::ToSnakeCase;
 use proc_macro2::TokenStream;
 use syn::{spanned::Spanned};
 
-use crate::util::{get_type_path, is_option_type, get_inner_type_path, quote_str_option, quote_int_option};
+use crate::util::{get_type_path, is_option_type, get_inner_type_path, get_relation_type, get_wrapper_names, is_snapshot_type, is_vec_type, quote_str_option, quote_int_option};
 
 #[derive(Debug, Clone, FromMeta)]
 struct ColumnAttr {
@@ -12,13 +12,146 @@ struct ColumnAttr {
     decimal_places: Option<usize>,
 }
 
+/// A list of column names, written as `columns("a", "b")`, `columns(a, b)`
+/// or `columns = "a, b"`.
+#[derive(Debug, Clone, Default)]
+struct ColumnList(Vec<String>);
+
+impl FromMeta for ColumnList {
+    fn from_list(items: &[syn::NestedMeta]) -> darling::Result<Self> {
+        items
+            .iter()
+            .map(|item| match item {
+                syn::NestedMeta::Lit(syn::Lit::Str(lit)) => Ok(lit.value()),
+                syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.get_ident().is_some() => {
+                    Ok(path.get_ident().unwrap().to_string())
+                }
+                _ => Err(darling::Error::unexpected_type("column").with_span(item)),
+            })
+            .collect::<darling::Result<Vec<String>>>()
+            .map(ColumnList)
+    }
+
+    fn from_string(value: &str) -> darling::Result<Self> {
+        Ok(ColumnList(value.split(',').map(|c| c.trim().to_owned()).filter(|c| !c.is_empty()).collect()))
+    }
+}
+
+#[derive(Debug, Clone, FromMeta)]
+struct IndexAttr {
+    columns: ColumnList,
+
+    #[darling(default)]
+    unique: bool,
+
+    name: Option<String>,
+
+    #[darling(rename = "where")]
+    condition: Option<String>,
+}
+
+#[derive(Debug, Clone, FromMeta)]
+struct ScopeAttr {
+    name: syn::Ident,
+
+    filter: Option<syn::Path>,
+}
+
+#[derive(Debug, Clone, FromMeta)]
+struct CheckAttr {
+    name: Option<String>,
+
+    expr: String,
+}
+
+#[derive(Debug, Clone, Default, FromMeta)]
+#[darling(default)]
+struct RelationAttr {
+    belongs_to: bool,
+
+    has_one: bool,
+
+    has_many: bool,
+
+    many_to_many: bool,
+
+    polymorphic: Option<darling::util::PathList>,
+
+    #[darling(rename = "as")]
+    morph_as: Option<String>,
+
+    type_column: Option<String>,
+
+    through: Option<String>,
+
+    join_local_key: Option<String>,
+
+    join_foreign_key: Option<String>,
+
+    local_key: Option<String>,
+
+    foreign_key: Option<String>,
+
+    on_delete: Option<String>,
+
+    on_update: Option<String>,
+
+    cascade: Option<String>,
+}
+
+/// A bound of the `range` validator, an integer, a float or a string for
+/// negative numbers.
+#[derive(Debug, Clone)]
+struct Number(f64);
+
+impl FromMeta for Number {
+    fn from_value(value: &syn::Lit) -> darling::Result<Self> {
+        let number = match value {
+            syn::Lit::Int(lit) => lit.base10_parse::<f64>(),
+            syn::Lit::Float(lit) => lit.base10_parse::<f64>(),
+            syn::Lit::Str(lit) => lit.value().trim().parse::<f64>().map_err(|e| syn::Error::new(lit.span(), e)),
+            _ => return Err(darling::Error::unexpected_lit_type(value)),
+        };
+
+        number.map(Number).map_err(|e| darling::Error::custom(e).with_span(value))
+    }
+}
+
+#[derive(Debug, Clone, FromMeta)]
+struct LengthAttr {
+    min: Option<usize>,
+
+    max: Option<usize>,
+}
+
+#[derive(Debug, Clone, FromMeta)]
+struct RangeAttr {
+    min: Option<Number>,
+
+    max: Option<Number>,
+}
+
+#[derive(Debug, Clone, Default, FromMeta)]
+#[darling(default)]
+struct ValidateAttr {
+    length: Option<LengthAttr>,
+
+    range: Option<RangeAttr>,
+
+    email: bool,
+
+    regex: Option<String>,
+}
+
 #[derive(Debug, Clone, FromField)]
-#[darling(attributes(column))]
+#[darling(attributes(column), forward_attrs(relation, validate))]
 struct EntityField {
     ident: Option<syn::Ident>,
 
     ty: syn::Type,
 
+    attrs: Vec<syn::Attribute>,
+
     rename: Option<String>,
 
     #[darling(rename = "type")]
@@ -40,6 +173,15 @@ struct EntityField {
     #[darling(default)]
     index: bool,
 
+    #[darling(default)]
+    version: bool,
+
+    #[darling(default)]
+    created_at: bool,
+
+    #[darling(default)]
+    updated_at: bool,
+
     #[darling(rename = "attrs")]
     attr: Option<ColumnAttr>,
 }
@@ -49,6 +191,99 @@ impl EntityField {
         self.ident.clone().expect("should have ident")
     }
 
+    fn is_relation(&self) -> bool {
+        self.attrs.iter().any(|attr| attr.path.is_ident("relation"))
+    }
+
+    fn is_snapshot(&self) -> bool {
+        !self.is_relation() && is_snapshot_type(&self.ty)
+    }
+
+    fn is_column(&self) -> bool {
+        !self.is_relation() && !self.is_snapshot()
+    }
+
+    fn get_relation(&self) -> syn::Result<Option<RelationAttr>> {
+        let attr = match self.attrs.iter().find(|attr| attr.path.is_ident("relation")) {
+            Some(attr) => attr,
+            None => return Ok(None),
+        };
+
+        let relation = match attr.parse_meta()? {
+            syn::Meta::Path(_) => RelationAttr::default(),
+            meta => RelationAttr::from_meta(&meta).map_err(|e| syn::Error::new(attr.span(), e.to_string()))?,
+        };
+
+        let kinds = [relation.belongs_to, relation.has_one, relation.has_many, relation.many_to_many, relation.polymorphic.is_some()];
+        if kinds.iter().filter(|kind| **kind).count() != 1 {
+            return Err(syn::Error::new(attr.span(), "expected one of `belongs_to`, `has_one`, `has_many`, `many_to_many` or `polymorphic` for `relation`"));
+        }
+        let many = relation.has_many || relation.many_to_many;
+        if many != is_vec_type(&self.ty) {
+            let message = match many {
+                true => "`has_many` and `many_to_many` relations require a `Vec` field",
+                false => "`belongs_to` and `has_one` relations can't be a `Vec` field",
+            };
+            return Err(syn::Error::new(self.get_ident().span(), message));
+        }
+        if relation.many_to_many != relation.through.is_some() {
+            return Err(syn::Error::new(attr.span(), "`through` is required by and only allowed for `many_to_many`"));
+        }
+        if relation.morph_as.is_some() && !relation.has_one && !relation.has_many {
+            return Err(syn::Error::new(attr.span(), "`as` is only allowed for `has_one` and `has_many`"));
+        }
+        if relation.type_column.is_some() && relation.polymorphic.is_none() && relation.morph_as.is_none() {
+            return Err(syn::Error::new(attr.span(), "`type_column` is only allowed for `polymorphic` and `as`"));
+        }
+        if let Some(types) = &relation.polymorphic {
+            if types.is_empty() {
+                return Err(syn::Error::new(attr.span(), "`polymorphic` requires at least one entity type"));
+            }
+            if get_wrapper_names(&self.ty).first().map(String::as_str) == Some("Lazy") {
+                return Err(syn::Error::new(self.get_ident().span(), "`polymorphic` relation can't be `Lazy`"));
+            }
+        }
+        if let Some(cascade) = &relation.cascade {
+            if cascade != "save" {
+                return Err(syn::Error::new(attr.span(), format!("unsupported cascade `{}`, expected `save`", cascade)));
+            }
+            if !relation.has_one && !relation.has_many {
+                return Err(syn::Error::new(attr.span(), "`cascade` is only allowed for `has_one` and `has_many`"));
+            }
+            if get_wrapper_names(&self.ty).first().map(String::as_str) == Some("Lazy") {
+                return Err(syn::Error::new(self.get_ident().span(), "cascaded relation can't be `Lazy`"));
+            }
+        }
+
+        Ok(Some(relation))
+    }
+
+    fn get_validators(&self) -> syn::Result<Vec<ValidateAttr>> {
+        let mut validators = vec![];
+
+        for attr in self.attrs.iter().filter(|attr| attr.path.is_ident("validate")) {
+            if !self.is_column() {
+                return Err(syn::Error::new(attr.span(), "`validate` is only allowed for columns"));
+            }
+            let validator = ValidateAttr::from_meta(&attr.parse_meta()?).map_err(|e| syn::Error::new(attr.span(), e.to_string()))?;
+            if let Some(regex) = &validator.regex {
+                if regex.is_empty() {
+                    return Err(syn::Error::new(attr.span(), "`regex` requires a pattern"));
+                }
+                if let Err(e) = regex::Regex::new(regex) {
+                    return Err(syn::Error::new(attr.span(), format!("invalid `regex` pattern: {}", e)));
+                }
+            }
+            validators.push(validator);
+        }
+
+        Ok(validators)
+    }
+
+    fn get_relation_type(&self) -> syn::Result<syn::Path> {
+        get_relation_type(&self.ty).ok_or_else(|| syn::Error::new(self.get_ident().span(), "unsupport relation type"))
+    }
+
     fn get_conlumn_name(&self) -> String {
         match self.rename {
             Some(ref rename) if !rename.is_empty() => {
@@ -61,7 +296,13 @@ impl EntityField {
     }
 
     fn get_column_kind(&self) -> syn::Result<(bool, TokenStream)> {
-        let mut null = false;
+        // the column is nullable for Option fields, even if the type is specified
+        let mut null = matches!(get_type_path(&self.ty), Some(type_path) if is_option_type(&type_path));
+        let timestamp = self.created_at || self.updated_at;
+        if timestamp && !matches!(self.column_type.as_deref(), None | Some("timestamp") | Some("datetime")) {
+            return Err(syn::Error::new(self.get_ident().span(), "`created_at` and `updated_at` columns must be of type `timestamp` or `datetime`"));
+        }
+
         let gen: TokenStream = if let Some(column_type) = self.column_type.clone() {
             match column_type.as_str() {
                 "integer" => {
@@ -91,6 +332,8 @@ impl EntityField {
                     return Err(syn::Error::new(self.get_ident().span(), "invalid type for `column`"));
                 }
             }
+        } else if timestamp {
+            quote::quote!(omi::model::DataKind::Timestamp)
         } else if let Some(mut type_path) = get_type_path(&self.ty) {
             if is_option_type(&type_path) {
                 null = true;
@@ -139,8 +382,28 @@ impl EntityField {
 struct DeriveEntity {
     ident: syn::Ident,
 
+    vis: syn::Visibility,
+
     table: Option<String>,
 
+    clock: Option<String>,
+
+    soft_delete: Option<String>,
+
+    default_scope: Option<syn::Path>,
+
+    #[darling(default)]
+    hooks: bool,
+
+    #[darling(multiple, rename = "scope")]
+    scopes: Vec<ScopeAttr>,
+
+    #[darling(multiple, rename = "index")]
+    indexes: Vec<IndexAttr>,
+
+    #[darling(multiple, rename = "check")]
+    checks: Vec<CheckAttr>,
+
     data: darling::ast::Data<darling::util::Ignored, EntityField>,
 
     #[darling(skip)]
@@ -167,6 +430,28 @@ impl DeriveEntity {
         let table_name = &self.get_table_name();
 
         let conlumns = &self.expand_columns()?;
+        let foreign_keys = &self.expand_foreign_keys()?;
+        let relations = &self.expand_relations()?;
+        let version = &quote_str_option(&self.get_version()?);
+        let created_at = &quote_str_option(&self.get_timestamp("created_at", |f| f.created_at)?);
+        let updated_at = &quote_str_option(&self.get_timestamp("updated_at", |f| f.updated_at)?);
+        let clock = &self.expand_clock()?;
+        let soft_delete = &quote_str_option(&self.get_soft_delete()?);
+        let values = &self.expand_values();
+        let set_values = &self.expand_set_values();
+        let snapshot = &self.expand_snapshot()?;
+        let validate = &self.expand_validate()?;
+        let load_relations = &self.expand_load_relations()?;
+        let save_relations = &self.expand_save_relations()?;
+        let indexes = &self.expand_indexes()?;
+        let checks = &self.expand_checks();
+        let default_scope = &self.expand_default_scope();
+        let scopes = &self.expand_scopes();
+        let hooks = self.hooks;
+        let hooks_impl = match hooks {
+            true => TokenStream::new(),
+            false => quote::quote!(impl omi::model::EntityHooks for #ident {}),
+        };
 
         let gen = quote::quote! {
             impl omi::model::Entity for #ident {
@@ -175,10 +460,42 @@ impl DeriveEntity {
                         table: omi::model::Table {
                             name: String::from(#table_name),
                             columns: #conlumns,
+                            foreign_keys: #foreign_keys,
+                            indexes: #indexes,
+                            checks: #checks,
                         },
+                        relations: #relations,
+                        version: #version,
+                        created_at: #created_at,
+                        updated_at: #updated_at,
+                        clock: #clock,
+                        soft_delete: #soft_delete,
+                        hooks: #hooks,
                     }
                 }
+
+                fn table_name() -> String {
+                    String::from(#table_name)
+                }
+
+                #values
+
+                #set_values
+
+                #snapshot
+
+                #validate
+
+                #default_scope
+
+                #load_relations
+
+                #save_relations
             }
+
+            #hooks_impl
+
+            #scopes
         };
 
         Ok(gen)
@@ -194,7 +511,7 @@ impl DeriveEntity {
     fn expand_columns(&self) -> syn::Result<TokenStream> {
         let mut tokens = Vec::new();
         
-        for field in self.fields.iter() {
+        for field in self.fields.iter().filter(|f| f.is_column()) {
             let token = Self::expand_colunn(field)?;
             tokens.push(token);
         }
@@ -206,6 +523,692 @@ impl DeriveEntity {
         Ok(gen)
     }
 
+    fn expand_foreign_keys(&self) -> syn::Result<TokenStream> {
+        let table_name = self.get_table_name();
+        let mut tokens = Vec::new();
+
+        for field in self.fields.iter() {
+            let relation = match field.get_relation()? {
+                Some(relation) if relation.belongs_to => relation,
+                _ => continue,
+            };
+
+            let target = field.get_relation_type()?;
+            let (local_key, foreign_key) = self.get_relation_keys(field, &relation);
+            let name = format!("fk_{}_{}", table_name, local_key);
+            let on_delete = Self::expand_action(field, &relation.on_delete)?;
+            let on_update = Self::expand_action(field, &relation.on_update)?;
+
+            tokens.push(quote::quote! {
+                omi::model::ForeignKey{
+                    name: String::from(#name),
+                    columns: vec![String::from(#local_key)],
+                    referenced_table: <#target as omi::model::Entity>::table_name(),
+                    referenced_columns: vec![String::from(#foreign_key)],
+                    on_delete: #on_delete,
+                    on_update: #on_update,
+                }
+            });
+        }
+
+        let gen = quote::quote! {
+            vec![#(#tokens),*]
+        };
+
+        Ok(gen)
+    }
+
+    fn expand_relations(&self) -> syn::Result<TokenStream> {
+        let mut tokens = Vec::new();
+
+        for field in self.fields.iter() {
+            let relation = match field.get_relation()? {
+                Some(relation) => relation,
+                None => continue,
+            };
+
+            let name = field.get_ident().to_string();
+            let target = field.get_relation_type()?;
+            let (local_key, foreign_key) = self.get_relation_keys(field, &relation);
+            if (relation.belongs_to || relation.polymorphic.is_some()) && !self.has_column(&local_key) {
+                return Err(syn::Error::new(field.get_ident().span(), format!("local key `{}` of relation `{}` is not a column", local_key, name)));
+            }
+            let kind = match () {
+                _ if relation.polymorphic.is_some() => quote::quote!(omi::model::RelationKind::MorphTo),
+                _ if relation.has_one => quote::quote!(omi::model::RelationKind::HasOne),
+                _ if relation.has_many => quote::quote!(omi::model::RelationKind::HasMany),
+                _ if relation.many_to_many => quote::quote!(omi::model::RelationKind::ManyToMany),
+                _ => quote::quote!(omi::model::RelationKind::BelongsTo),
+            };
+            let lazy = get_wrapper_names(&field.ty).first().map(String::as_str) == Some("Lazy");
+            let cascade = relation.cascade.is_some();
+            let through = match self.expand_join_table(field, &relation)? {
+                Some(join_table) => quote::quote!(Some(#join_table)),
+                None => quote::quote!(None),
+            };
+
+            let morph = match (self.get_type_column(field, &relation), &relation.polymorphic) {
+                (Some(type_column), Some(types)) => {
+                    let types = types.iter();
+                    quote::quote!(Some(omi::model::Morph{
+                        type_column: String::from(#type_column),
+                        types: vec![#(<#types as omi::model::Entity>::table_name()),*],
+                    }))
+                }
+                (Some(type_column), None) => {
+                    let table_name = self.get_table_name();
+                    quote::quote!(Some(omi::model::Morph{
+                        type_column: String::from(#type_column),
+                        types: vec![String::from(#table_name)],
+                    }))
+                }
+                _ => quote::quote!(None),
+            };
+            let target = match relation.polymorphic {
+                Some(_) => quote::quote!(String::new()),
+                None => quote::quote!(<#target as omi::model::Entity>::table_name()),
+            };
+
+            tokens.push(quote::quote! {
+                omi::model::Relation{
+                    name: String::from(#name),
+                    kind: #kind,
+                    target: #target,
+                    foreign_key: String::from(#foreign_key),
+                    local_key: String::from(#local_key),
+                    through: #through,
+                    morph: #morph,
+                    cascade: #cascade,
+                    lazy: #lazy,
+                }
+            });
+        }
+
+        let gen = quote::quote! {
+            vec![#(#tokens),*]
+        };
+
+        Ok(gen)
+    }
+
+    fn expand_values(&self) -> TokenStream {
+        let arms = self.fields.iter().filter(|f| f.is_column()).map(|field| {
+            let ident = field.get_ident();
+            let name = field.get_conlumn_name();
+
+            quote::quote! {
+                #name => Some(omi::model::ToValue::to_value(&self.#ident)),
+            }
+        });
+
+        quote::quote! {
+            fn value(&self, column: &str) -> Option<omi::model::Value> {
+                match column {
+                    #(#arms)*
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    fn expand_set_values(&self) -> TokenStream {
+        let arms = self.fields.iter().filter(|f| f.is_column()).map(|field| {
+            let ident = field.get_ident();
+            let name = field.get_conlumn_name();
+
+            quote::quote! {
+                #name => match omi::model::FromValue::from_value(value) {
+                    Some(value) => {
+                        self.#ident = value;
+                        true
+                    }
+                    None => false,
+                },
+            }
+        });
+
+        quote::quote! {
+            fn set_value(&mut self, column: &str, value: omi::model::Value) -> bool {
+                match column {
+                    #(#arms)*
+                    _ => false,
+                }
+            }
+        }
+    }
+
+    /// The `#[validate(...)]` validators of the fields, checked against the
+    /// value of the column.
+    fn expand_validate(&self) -> syn::Result<TokenStream> {
+        let mut checks = Vec::new();
+
+        for field in self.fields.iter() {
+            let ident = field.get_ident();
+            let name = field.get_conlumn_name();
+
+            for validator in field.get_validators()? {
+                let mut validators = Vec::new();
+                if let Some(length) = &validator.length {
+                    let min = quote_int_option(&length.min);
+                    let max = quote_int_option(&length.max);
+                    validators.push(quote::quote!(omi::validation::length(&value, #min, #max)));
+                }
+                if let Some(range) = &validator.range {
+                    let min = quote_int_option(&range.min.as_ref().map(|n| n.0));
+                    let max = quote_int_option(&range.max.as_ref().map(|n| n.0));
+                    validators.push(quote::quote!(omi::validation::range(&value, #min, #max)));
+                }
+                if validator.email {
+                    validators.push(quote::quote!(omi::validation::email(&value)));
+                }
+                // Compiled once on first use, the pattern was checked by the derive.
+                if let Some(regex) = &validator.regex {
+                    validators.push(quote::quote!({
+                        static PATTERN: omi::validation::Lazy<omi::validation::Regex> =
+                            omi::validation::Lazy::new(|| omi::validation::Regex::new(#regex).expect("checked by the derive"));
+                        omi::validation::regex(&value, &PATTERN)
+                    }));
+                }
+
+                checks.push(quote::quote! {
+                    let value = omi::model::ToValue::to_value(&self.#ident);
+                    #(errors.check(#name, #validators);)*
+                });
+            }
+        }
+
+        if checks.is_empty() {
+            return Ok(TokenStream::new());
+        }
+
+        Ok(quote::quote! {
+            fn validate(&self, errors: &mut omi::validation::ValidationErrors) {
+                #(#checks)*
+            }
+        })
+    }
+
+    /// The version column for optimistic locking, an integer field marked
+    /// with `#[column(version)]`.
+    fn get_version(&self) -> syn::Result<Option<String>> {
+        let mut fields = self.fields.iter().filter(|f| f.version && f.is_column());
+        let field = match fields.next() {
+            Some(field) => field,
+            None => return Ok(None),
+        };
+        if let Some(field) = fields.next() {
+            return Err(syn::Error::new(field.get_ident().span(), "entity can only have one `version` column"));
+        }
+
+        let integer = matches!(
+            get_type_path(&field.ty).and_then(|path| path.get_ident().map(|ident| ident.to_string())),
+            Some(name) if ["i8", "u8", "i16", "u16", "i32", "u32", "i64", "u64", "isize", "usize"].contains(&name.as_str())
+        );
+        if !integer {
+            return Err(syn::Error::new(field.get_ident().span(), "`version` column must be an integer"));
+        }
+
+        Ok(Some(field.get_conlumn_name()))
+    }
+
+    /// The `created_at` or `updated_at` column, marked by the attribute.
+    fn get_timestamp(&self, attr: &str, marked: fn(&EntityField) -> bool) -> syn::Result<Option<String>> {
+        let mut fields = self.fields.iter().filter(|f| marked(f) && f.is_column());
+        let field = match fields.next() {
+            Some(field) => field,
+            None => return Ok(None),
+        };
+        if let Some(field) = fields.next() {
+            return Err(syn::Error::new(field.get_ident().span(), format!("entity can only have one `{}` column", attr)));
+        }
+        if field.created_at && field.updated_at {
+            return Err(syn::Error::new(field.get_ident().span(), "column can't be both `created_at` and `updated_at`"));
+        }
+
+        Ok(Some(field.get_conlumn_name()))
+    }
+
+    fn expand_default_scope(&self) -> TokenStream {
+        match &self.default_scope {
+            Some(path) => quote::quote! {
+                fn default_scope() -> Option<omi::statement::Filters> {
+                    Some(#path().into())
+                }
+            },
+            None => TokenStream::new(),
+        }
+    }
+
+    /// The named scopes as methods of `SelectStatement<T>`, in a trait named
+    /// `{Entity}Scopes`. The filter of a scope defaults to the associated
+    /// function of the entity with the same name.
+    fn expand_scopes(&self) -> TokenStream {
+        if self.scopes.is_empty() {
+            return TokenStream::new();
+        }
+
+        let ident = &self.ident;
+        let vis = &self.vis;
+        let trait_ident = quote::format_ident!("{}Scopes", ident);
+        let names: Vec<&syn::Ident> = self.scopes.iter().map(|scope| &scope.name).collect();
+        let filters = self.scopes.iter().map(|scope| match &scope.filter {
+            Some(path) => quote::quote!(#path),
+            None => {
+                let name = &scope.name;
+                quote::quote!(<#ident>::#name)
+            }
+        });
+
+        quote::quote! {
+            #vis trait #trait_ident {
+                #(fn #names(&mut self) -> &mut Self;)*
+            }
+
+            impl #trait_ident for omi::statement::SelectStatement<#ident> {
+                #(fn #names(&mut self) -> &mut Self {
+                    self.filter(#filters())
+                })*
+            }
+        }
+    }
+
+    /// The soft delete column, which must be a nullable column of the entity.
+    fn get_soft_delete(&self) -> syn::Result<Option<String>> {
+        let column = match &self.soft_delete {
+            Some(column) => column,
+            None => return Ok(None),
+        };
+
+        let field = self.fields.iter().find(|f| f.is_column() && f.get_conlumn_name() == *column);
+        match field {
+            Some(field) if field.get_column_kind()?.0 => Ok(Some(column.to_owned())),
+            Some(field) => Err(syn::Error::new(field.get_ident().span(), "`soft_delete` column must be an `Option`")),
+            None => Err(syn::Error::new(self.ident.span(), format!("`soft_delete` column `{}` is not a column of the entity", column))),
+        }
+    }
+
+    fn expand_clock(&self) -> syn::Result<TokenStream> {
+        match self.clock.as_deref() {
+            None | Some("database") => Ok(quote::quote!(omi::model::Clock::Database)),
+            Some("app") | Some("application") => Ok(quote::quote!(omi::model::Clock::Application)),
+            Some(clock) => Err(syn::Error::new(self.ident.span(), format!("unsupported clock `{}`, expected `database` or `app`", clock))),
+        }
+    }
+
+    fn expand_snapshot(&self) -> syn::Result<TokenStream> {
+        let mut fields = self.fields.iter().filter(|f| f.is_snapshot());
+        let ident = match fields.next() {
+            Some(field) => field.get_ident(),
+            None => return Ok(TokenStream::new()),
+        };
+        if let Some(field) = fields.next() {
+            return Err(syn::Error::new(field.get_ident().span(), "entity can only have one `Snapshot` field"));
+        }
+
+        Ok(quote::quote! {
+            fn snapshot(&self) -> Option<&omi::model::Snapshot> {
+                Some(&self.#ident)
+            }
+
+            fn take_snapshot(&mut self) {
+                self.#ident = omi::model::Snapshot::of(&*self);
+            }
+        })
+    }
+
+    /// Insert the relations marked with `cascade = "save"`, after writing the
+    /// key of this entity into their foreign key.
+    fn expand_save_relations(&self) -> syn::Result<TokenStream> {
+        let mut tokens = Vec::new();
+
+        for field in self.fields.iter() {
+            let relation = match field.get_relation()? {
+                Some(relation) if relation.cascade.is_some() => relation,
+                _ => continue,
+            };
+
+            let ident = field.get_ident();
+            let (local_key, foreign_key) = self.get_relation_keys(field, &relation);
+            let morph = match self.get_type_column(field, &relation) {
+                Some(type_column) => {
+                    let table_name = self.get_table_name();
+                    quote::quote!(Some((#type_column, String::from(#table_name))))
+                }
+                None => quote::quote!(None),
+            };
+
+            let wrappers = get_wrapper_names(&field.ty);
+            let related = match wrappers.iter().map(String::as_str).collect::<Vec<&str>>().as_slice() {
+                ["Vec", ..] => quote::quote!(Some(&mut self.#ident[..])),
+                ["Option", "Box", ..] => quote::quote!(self.#ident.as_deref_mut().map(std::slice::from_mut)),
+                ["Option", ..] => quote::quote!(self.#ident.as_mut().map(std::slice::from_mut)),
+                ["Box", ..] => quote::quote!(Some(std::slice::from_mut(&mut *self.#ident))),
+                _ => quote::quote!(Some(std::slice::from_mut(&mut self.#ident))),
+            };
+
+            tokens.push(quote::quote! {
+                let key = omi::model::Entity::value(&*self, #local_key);
+                if let Some(related) = #related {
+                    omi::statement::insert_related(related, #foreign_key, key, #morph, db)?;
+                }
+            });
+        }
+
+        if tokens.is_empty() {
+            return Ok(TokenStream::new());
+        }
+
+        Ok(quote::quote! {
+            fn save_relations(&mut self, db: &omi::Database) -> omi::Result<()> {
+                #(#tokens)*
+
+                Ok(())
+            }
+        })
+    }
+
+    fn expand_load_relations(&self) -> syn::Result<TokenStream> {
+        let mut tokens = Vec::new();
+
+        for field in self.fields.iter() {
+            let relation = match field.get_relation()? {
+                Some(relation) => relation,
+                None => continue,
+            };
+
+            if relation.polymorphic.is_some() {
+                tokens.push(self.expand_load_morph_to(field, &relation)?);
+                continue;
+            }
+
+            let ident = field.get_ident();
+            let name = ident.to_string();
+            let target = field.get_relation_type()?;
+            let (local_key, foreign_key) = self.get_relation_keys(field, &relation);
+            let lazy = get_wrapper_names(&field.ty).first().map(String::as_str) == Some("Lazy");
+            let related = Self::expand_related_conversion(field);
+
+            // Many to many relations are matched through the rows of the join
+            // table, and polymorphic children by the type column as well.
+            let groups = match (self.expand_join_table(field, &relation)?, self.get_type_column(field, &relation)) {
+                (Some(join_table), _) => quote::quote! {
+                    omi::statement::load_through::<#target>(&#join_table, #foreign_key, keys, &nested, db)?
+                },
+                (None, Some(type_column)) => {
+                    let table_name = self.get_table_name();
+                    quote::quote! {
+                        omi::statement::group_by_key(
+                            omi::statement::load_morphed::<#target>(#type_column, #table_name, #foreign_key, keys, &nested, db)?,
+                            #foreign_key,
+                        )
+                    }
+                }
+                (None, None) => quote::quote! {
+                    omi::statement::group_by_key(
+                        omi::statement::load_related::<#target>(#foreign_key, keys, &nested, db)?,
+                        #foreign_key,
+                    )
+                },
+            };
+
+            if !lazy {
+                tokens.push(quote::quote! {
+                    if let Some(nested) = omi::statement::nested_paths(paths, #name) {
+                        let keys = omi::statement::collect_keys(entities, #local_key);
+                        let groups = #groups;
+
+                        for entity in entities.iter_mut() {
+                            let related = omi::model::Entity::value(&*entity, #local_key)
+                                .and_then(|key| groups.get(&key.to_sql()))
+                                .cloned()
+                                .unwrap_or_default();
+                            entity.#ident = #related;
+                        }
+                    }
+                });
+                continue;
+            }
+
+            // Lazy relations are loaded with the parent only when included,
+            // otherwise they get a loader for the key of each entity.
+            tokens.push(quote::quote! {
+                if let Some(nested) = omi::statement::nested_paths(paths, #name) {
+                    let keys = omi::statement::collect_keys(entities, #local_key);
+                    let groups = #groups;
+
+                    for entity in entities.iter_mut() {
+                        let related = omi::model::Entity::value(&*entity, #local_key)
+                            .and_then(|key| groups.get(&key.to_sql()))
+                            .cloned()
+                            .unwrap_or_default();
+                        entity.#ident = omi::model::Lazy::loaded(#related);
+                    }
+                } else {
+                    for entity in entities.iter_mut() {
+                        let key = omi::model::Entity::value(&*entity, #local_key).filter(|key| !key.is_null());
+
+                        entity.#ident = omi::model::Lazy::new(move |db| {
+                            let keys: Vec<omi::model::Value> = key.clone().into_iter().collect();
+                            let nested: Vec<String> = vec![];
+                            let groups = #groups;
+                            let related = key.as_ref()
+                                .and_then(|key| groups.get(&key.to_sql()))
+                                .cloned()
+                                .unwrap_or_default();
+                            Ok(#related)
+                        });
+                    }
+                }
+            });
+        }
+
+        if tokens.is_empty() {
+            return Ok(TokenStream::new());
+        }
+
+        let gen = quote::quote! {
+            fn load_relations(entities: &mut [Self], paths: &[String], db: &omi::Database) -> omi::Result<()> {
+                #(#tokens)*
+
+                Ok(())
+            }
+        };
+
+        Ok(gen)
+    }
+
+    /// Convert the matched entities, a `Vec` named `related`, into the type of
+    /// the field.
+    fn expand_related_conversion(field: &EntityField) -> TokenStream {
+        let wrappers = get_wrapper_names(&field.ty);
+        match wrappers.iter().map(String::as_str).skip_while(|name| *name == "Lazy").collect::<Vec<&str>>().as_slice() {
+            ["Vec", ..] => quote::quote!(related),
+            ["Option", "Box", ..] => quote::quote!(related.into_iter().next().map(Box::new)),
+            ["Option", ..] => quote::quote!(related.into_iter().next()),
+            ["Box", ..] => quote::quote!(Box::new(related.into_iter().next().unwrap_or_default())),
+            _ => quote::quote!(related.into_iter().next().unwrap_or_default()),
+        }
+    }
+
+    /// Load a `polymorphic` relation, the entities are grouped by the type
+    /// column and each entity type is loaded with one query, then wrapped in
+    /// the variant of the field enum named after it.
+    fn expand_load_morph_to(&self, field: &EntityField, relation: &RelationAttr) -> syn::Result<TokenStream> {
+        let ident = field.get_ident();
+        let name = ident.to_string();
+        let morph = field.get_relation_type()?;
+        let type_column = self.get_type_column(field, relation).unwrap_or_default();
+        let (local_key, foreign_key) = self.get_relation_keys(field, relation);
+        let related = Self::expand_related_conversion(field);
+
+        let mut loads = Vec::new();
+        for target in relation.polymorphic.iter().flat_map(|types| types.iter()) {
+            let variant = &target.segments.last().expect("should have ident").ident;
+
+            loads.push(quote::quote! {
+                let type_value = <#target as omi::model::Entity>::table_name();
+                let keys = omi::statement::collect_morph_keys(entities, #type_column, &type_value, #local_key);
+                let groups = omi::statement::group_by_key(
+                    omi::statement::load_related::<#target>(#foreign_key, keys, &nested, db)?,
+                    #foreign_key,
+                );
+
+                for entity in entities.iter_mut() {
+                    if omi::statement::is_morph_type(&*entity, #type_column, &type_value) {
+                        let related: Vec<#morph> = omi::model::Entity::value(&*entity, #local_key)
+                            .and_then(|key| groups.get(&key.to_sql()))
+                            .cloned()
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(#morph::#variant)
+                            .collect();
+                        entity.#ident = #related;
+                    }
+                }
+            });
+        }
+
+        Ok(quote::quote! {
+            if let Some(nested) = omi::statement::nested_paths(paths, #name) {
+                #({ #loads })*
+            }
+        })
+    }
+
+    /// The type column of a polymorphic relation, `{field}_type` for the
+    /// `polymorphic` side and `{as}_type` for the `as` side by default.
+    fn get_type_column(&self, field: &EntityField, relation: &RelationAttr) -> Option<String> {
+        let default = match (&relation.polymorphic, &relation.morph_as) {
+            (Some(_), _) => format!("{}_type", field.get_ident()),
+            (None, Some(morph_as)) => format!("{}_type", morph_as),
+            (None, None) => return None,
+        };
+
+        Some(relation.type_column.clone().unwrap_or(default))
+    }
+
+    /// The join table of a `many_to_many` relation, the join keys default to
+    /// `{entity}_id` and `{target}_id`.
+    fn expand_join_table(&self, field: &EntityField, relation: &RelationAttr) -> syn::Result<Option<TokenStream>> {
+        let table = match &relation.through {
+            Some(table) => table,
+            None => return Ok(None),
+        };
+
+        let target = field.get_relation_type()?;
+        let target_name = target.segments.last().map(|s| s.ident.to_string()).unwrap_or_default();
+        let local_key = relation.join_local_key.clone()
+            .unwrap_or_else(|| format!("{}_id", self.ident.to_string().to_snake_case()));
+        let foreign_key = relation.join_foreign_key.clone()
+            .unwrap_or_else(|| format!("{}_id", target_name.to_snake_case()));
+
+        Ok(Some(quote::quote! {
+            omi::model::JoinTable{
+                table: String::from(#table),
+                local_key: String::from(#local_key),
+                foreign_key: String::from(#foreign_key),
+            }
+        }))
+    }
+
+    /// The local key on this table and the foreign key on the target table
+    /// of the relation. `belongs_to` defaults to `{field}_id` and `id`,
+    /// `has_one` and `has_many` default to the primary key and `{entity}_id`
+    /// or `{as}_id`, `polymorphic` defaults to `{field}_id` and `id`,
+    /// and `many_to_many` defaults to the primary key and `id`.
+    fn get_relation_keys(&self, field: &EntityField, relation: &RelationAttr) -> (String, String) {
+        let (local_key, foreign_key) = match () {
+            _ if relation.has_one || relation.has_many => {
+                let owner = match &relation.morph_as {
+                    Some(morph_as) => morph_as.to_owned(),
+                    None => self.ident.to_string().to_snake_case(),
+                };
+                (self.get_primary_key(), format!("{}_id", owner))
+            }
+            _ if relation.many_to_many => (self.get_primary_key(), String::from("id")),
+            _ => (format!("{}_id", field.get_ident()), String::from("id")),
+        };
+
+        (relation.local_key.clone().unwrap_or(local_key), relation.foreign_key.clone().unwrap_or(foreign_key))
+    }
+
+    fn has_column(&self, name: &str) -> bool {
+        self.fields.iter().any(|f| f.is_column() && f.get_conlumn_name() == name)
+    }
+
+    fn get_primary_key(&self) -> String {
+        match self.fields.iter().find(|f| f.primary && f.is_column()) {
+            Some(field) => field.get_conlumn_name(),
+            None => String::from("id"),
+        }
+    }
+
+    fn expand_indexes(&self) -> syn::Result<TokenStream> {
+        let table_name = self.get_table_name();
+        let mut tokens = Vec::new();
+
+        for index in self.indexes.iter() {
+            let columns = &index.columns.0;
+            if columns.is_empty() {
+                return Err(syn::Error::new(self.ident.span(), "`index` requires at least one column"));
+            }
+
+            let name = index.name.clone().unwrap_or_else(|| format!("idx_{}_{}", table_name, columns.join("_")));
+            let unique = index.unique;
+            let condition = quote_str_option(&index.condition);
+
+            tokens.push(quote::quote! {
+                omi::model::Index{
+                    name: String::from(#name),
+                    columns: vec![#(String::from(#columns)),*],
+                    unique: #unique,
+                    condition: #condition,
+                }
+            });
+        }
+
+        let gen = quote::quote! {
+            vec![#(#tokens),*]
+        };
+
+        Ok(gen)
+    }
+
+    fn expand_checks(&self) -> TokenStream {
+        let table_name = self.get_table_name();
+        let tokens = self.checks.iter().enumerate().map(|(i, check)| {
+            let name = check.name.clone().unwrap_or_else(|| format!("chk_{}_{}", table_name, i + 1));
+            let expression = &check.expr;
+
+            quote::quote! {
+                omi::model::Check{
+                    name: String::from(#name),
+                    expression: String::from(#expression),
+                }
+            }
+        });
+
+        quote::quote! {
+            vec![#(#tokens),*]
+        }
+    }
+
+    fn expand_action(field: &EntityField, action: &Option<String>) -> syn::Result<TokenStream> {
+        let action = action.clone().unwrap_or_else(|| String::from("no_action"));
+        let gen = match action.to_ascii_lowercase().replace(' ', "_").as_str() {
+            "no_action" => quote::quote!(omi::model::ReferentialAction::NoAction),
+            "restrict" => quote::quote!(omi::model::ReferentialAction::Restrict),
+            "cascade" => quote::quote!(omi::model::ReferentialAction::Cascade),
+            "set_null" => quote::quote!(omi::model::ReferentialAction::SetNull),
+            "set_default" => quote::quote!(omi::model::ReferentialAction::SetDefault),
+            _ => {
+                return Err(syn::Error::new(field.get_ident().span(), "invalid action for `relation`"));
+            }
+        };
+
+        Ok(gen)
+    }
+
     fn expand_colunn(field: &EntityField) -> syn::Result<TokenStream> {
         let name = field.get_conlumn_name();
         let (null, column_kind) = field.get_column_kind()?;
@@ -16,6 +16,32 @@ mod util;
 /// `#[entity[table = "table_name"]]`
 /// specify table name for Entity, default is the snake case of struct name
 /// 
+/// `#[entity(index(columns("tenant_id", "slug"), unique, name = "idx_name", where = "deleted_at IS NULL"))]`
+/// a table level index over one or more columns, may be repeated. `unique`,
+/// `name` and the partial index predicate `where` are optional, the default
+/// name is `idx_{table}_{columns}`
+/// 
+/// `#[entity(check(expr = "price >= 0", name = "chk_name"))]`
+/// a table level check constraint, may be repeated, the default name is
+/// `chk_{table}_{n}`
+/// 
+/// `#[entity(soft_delete = "deleted_at")]`
+/// the nullable column set to the current time when the entity is deleted,
+/// queries leave out the rows where it's set
+/// 
+/// `#[entity(default_scope = "tenant_scope")]`
+/// the function whose filter is added to every query, bulk update and delete
+/// of the entity, unless the statement is `unscoped()`
+/// 
+/// `#[entity(scope(name = "published", filter = "published_filter"))]`
+/// a named scope, may be repeated, called as a method of `SelectStatement`
+/// through the generated `{Entity}Scopes` trait. The filter function defaults
+/// to the associated function of the entity with the same name
+/// 
+/// `#[entity(hooks)]`
+/// the entity implements `EntityHooks` itself, otherwise it's implemented
+/// without callbacks
+/// 
 /// `#[column(primary)]`
 /// primary key
 /// 
@@ -41,6 +67,67 @@ mod util;
 /// `#[column(attrs(max_digits = 8, decimal_places = 2))]`
 /// the decimal format of float type
 /// 
+/// `#[column(version)]`
+/// the integer version column for optimistic locking, updates and deletes of
+/// the entity only match the version it was loaded with, and updates bump it
+/// 
+/// `#[column(created_at)]`, `#[column(updated_at)]`
+/// set the column to the current time on insert, and on insert and update for
+/// `updated_at`, the column type is `timestamp` unless it's `datetime`
+/// 
+/// `#[entity(clock = "app")]`
+/// the clock of the `created_at` and `updated_at` columns, option value is
+/// `database` for `CURRENT_TIMESTAMP` and `app` for the UTC time of the
+/// application, default is `database`
+/// 
+/// `#[relation(belongs_to)]`
+/// mark the field as a relation to another entity, it's not mapped to a column.
+/// a `belongs_to` relation adds a foreign key constraint to the table
+/// 
+/// `#[relation(belongs_to, local_key = "brand_id", foreign_key = "id")]`
+/// the column of this entity that references the other entity, default is the
+/// field name with `_id` suffix, and the referenced column, default is `id`
+/// 
+/// `#[relation(has_many)]`
+/// mark a `Vec` field as the entities that reference this entity, the local
+/// key defaults to the primary key and the foreign key to `{entity}_id`
+/// 
+/// `#[relation(has_one)]`
+/// like `has_many`, but for a single entity that references this entity
+/// 
+/// `#[relation(many_to_many, through = "product_tags", join_local_key = "product_id", join_foreign_key = "tag_id")]`
+/// mark a `Vec` field as the entities related through the join table, the
+/// join keys default to `{entity}_id` and `{target}_id`, rows of the join table
+/// are added and removed with `attach()` and `detach()`
+/// 
+/// `#[relation(polymorphic(Product, Article), type_column = "commentable_type")]`
+/// mark a field of an enum with a variant per entity, named after the entity,
+/// as the entity referenced by the key and the type column, which holds the
+/// table name of the entity, the type column defaults to `{field}_type`
+/// 
+/// `#[relation(has_many, as = "commentable")]`
+/// the other side of a `polymorphic` relation, the foreign key defaults to
+/// `{as}_id` and the type column to `{as}_type`
+/// 
+/// `#[relation(has_many, cascade = "save")]`
+/// insert the related entities with this entity in one transaction, writing
+/// the generated key into their foreign key, only for `has_one` and `has_many`
+/// 
+/// a relation field of type `Lazy<T>` is not loaded with the entity unless it's
+/// included, call `load(db)` on it to load it on first access
+/// 
+/// `#[relation(belongs_to, on_delete = "cascade", on_update = "restrict")]`
+/// the actions of the foreign key constraint, option value is `no_action`,
+/// `restrict`, `cascade`, `set_null`, `set_default`, default is `no_action`
+/// 
+/// `#[validate(length(min = 1, max = 255), email, range(min = 0), regex = "^[a-z-]+$")]`
+/// check the field before the entity is inserted or updated, failures return
+/// `OmiError::Validation` with the messages of each field. text columns with a
+/// `size` are checked against it without a validator, `None` passes them all
+/// 
+/// a field of type `Snapshot` is not mapped to a column, it tracks the loaded
+/// values so that `update(Some(entity))` only saves the changed fields
+/// 
 /// field type with Option will make the column nullable
 /// 
 /// Example:
@@ -68,7 +155,7 @@ mod util;
 /// }
 /// ```
 /// 
-#[proc_macro_derive(Entity, attributes(entity, column, relation))]
+#[proc_macro_derive(Entity, attributes(entity, column, relation, validate))]
 pub fn derive_entity(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
     entity::impl_entity(input)
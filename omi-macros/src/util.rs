@@ -28,6 +28,57 @@ pub fn get_inner_type_path(type_path: &syn::Path) -> Option<syn::Path> {
     None
 }
 
+/// The generic wrappers allowed around the entity type of relation fields.
+const WRAPPERS: &[&str] = &["Lazy", "Option", "Box", "Vec"];
+
+/// Unwrap the wrappers of a relation field type, giving their names from the
+/// outside in and the entity type, e.g. `Option<Box<Brand>>` gives
+/// `["Option", "Box"]` and `Brand`.
+fn unwrap_relation_type(ty: &syn::Type) -> (Vec<String>, Option<syn::Path>) {
+    let mut names = vec![];
+    let mut type_path = get_type_path(ty);
+
+    while let Some(segment) = type_path.as_ref().and_then(|path| path.segments.last().cloned()) {
+        let name = segment.ident.to_string();
+        if !WRAPPERS.contains(&name.as_str()) {
+            break;
+        }
+        names.push(name);
+        type_path = match segment.arguments {
+            PathArguments::AngleBracketed(AngleBracketedGenericArguments { ref args, .. }) => match args.first() {
+                Some(GenericArgument::Type(ty)) => get_type_path(ty),
+                _ => None,
+            },
+            _ => None,
+        };
+    }
+
+    (names, type_path)
+}
+
+/// Whether the type is a `Vec`, possibly `Lazy`, e.g. the field of a
+/// `has_many` relation.
+pub fn is_vec_type(ty: &syn::Type) -> bool {
+    get_wrapper_names(ty).iter().find(|name| *name != "Lazy").map(String::as_str) == Some("Vec")
+}
+
+/// Whether the type is `Snapshot`, the field that tracks the loaded values.
+pub fn is_snapshot_type(ty: &syn::Type) -> bool {
+    matches!(get_type_path(ty).and_then(|path| path.segments.last().cloned()), Some(segment) if segment.ident == "Snapshot" && segment.arguments.is_empty())
+}
+
+/// The names of the generic wrappers of the type from the outside in, e.g.
+/// `Lazy<Option<Brand>>` gives `["Lazy", "Option"]`.
+pub fn get_wrapper_names(ty: &syn::Type) -> Vec<String> {
+    unwrap_relation_type(ty).0
+}
+
+/// Get the entity type of a relation field, unwrapping `Lazy`, `Option`,
+/// `Box` and `Vec`, e.g. `Option<Box<Brand>>` gives `Brand`.
+pub fn get_relation_type(ty: &syn::Type) -> Option<syn::Path> {
+    unwrap_relation_type(ty).1
+}
+
 pub fn quote_int_option<T: quote::ToTokens>(val: &Option<T>) -> TokenStream {
     if let Some(val) = val {
         quote::quote!(Some(#val))
@@ -1,7 +1,9 @@
 use sql_query_builder as sql;
 
 use crate::model::Entity;
+use crate::order::Direction;
 use crate::statement::*;
+use crate::Result;
 
 pub struct Builder {}
 
@@ -19,11 +21,44 @@ impl Builder {
         }
     }
 
-    fn build_delete_sql<T>(_statement: DeleteStatement<T>) -> String
+    fn build_delete_sql<T>(statement: DeleteStatement<T>) -> String
     where
         T: Entity,
     {
-        sql::Delete::new().as_string()
+        let meta = T::meta();
+        let deleted_at = match (&meta.soft_delete, statement.mode) {
+            (Some(column), DeleteMode::Delete) => Some((column, meta.clock.now_sql())),
+            (Some(column), DeleteMode::Restore) => Some((column, String::from("NULL"))),
+            _ => None,
+        };
+
+        // A soft delete or a restore updates the column instead.
+        if let Some((column, value)) = deleted_at {
+            let mut update = sql::Update::new()
+                .update(&meta.table.name)
+                .set(&format!("{} = {}", column, value));
+            if let Some(version) = &meta.version {
+                update = update.set(&format!("{} = {} + 1", version, version));
+            }
+            for filters in conditions(statement.conditions()) {
+                update = update.where_clause(&filters.to_sql());
+            }
+            if statement.returning {
+                update = update.raw_after(sql::UpdateClause::Where, "RETURNING *");
+            }
+
+            return update.as_string();
+        }
+
+        let mut delete = sql::Delete::new().delete_from(&meta.table.name);
+        for filters in conditions(statement.conditions()) {
+            delete = delete.where_clause(&filters.to_sql());
+        }
+        if statement.returning {
+            delete = delete.raw_after(sql::DeleteClause::Where, "RETURNING *");
+        }
+
+        delete.as_string()
     }
 
     fn build_raw_sql<T>(statement: RawStatement<T>) -> String
@@ -33,28 +68,184 @@ impl Builder {
         sql::Select::new().raw(&statement.sql).as_string()
     }
 
-    fn build_insert_sql<T>(_statement: InsertStatement<T>) -> String
+    fn build_insert_sql<T>(statement: InsertStatement<T>) -> String
     where
         T: Entity,
     {
-        sql::Insert::new().as_string()
+        let meta = T::meta();
+        let timestamps = [&meta.created_at, &meta.updated_at];
+
+        let mut columns = vec![];
+        let mut values = vec![];
+        for column in meta.table.columns.iter().filter(|column| !column.auto) {
+            let value = match timestamps.contains(&&Some(column.name.clone())) {
+                true => meta.clock.now_sql(),
+                false => match statement.entity.value(&column.name) {
+                    Some(value) => value.to_sql(),
+                    None => continue,
+                },
+            };
+            columns.push(column.name.as_str());
+            values.push(value);
+        }
+
+        let mut insert = sql::Insert::new()
+            .insert_into(&format!("{} ({})", meta.table.name, columns.join(", ")))
+            .values(&format!("({})", values.join(", ")));
+        if statement.returning {
+            insert = insert.raw_after(sql::InsertClause::Values, "RETURNING *");
+        }
+
+        insert.as_string()
     }
 
-    fn build_select_sql<T>(_statement: SelectStatement<T>) -> String
+    fn build_select_sql<T>(statement: SelectStatement<T>) -> String
     where
         T: Entity,
     {
-        let meta = T::meta();
-        sql::Select::new()
-            .select("*")
-            .from(&meta.table.name)
-            .as_string()
+        let mut select = sql::Select::new().select("*").from(&T::table_name());
+        for filters in &statement.filters {
+            select = select.where_clause(&filters.to_sql());
+        }
+        if let (true, Some(scope)) = (statement.scoped, T::default_scope()) {
+            select = select.where_clause(&scope.to_sql());
+        }
+        if let Some(column) = T::meta().soft_delete {
+            match statement.deleted {
+                SoftDeleted::Excluded => {
+                    select = select.where_clause(&format!("{} IS NULL", column))
+                }
+                SoftDeleted::Only => {
+                    select = select.where_clause(&format!("{} IS NOT NULL", column))
+                }
+                SoftDeleted::Included => {}
+            }
+        }
+        if !statement.groups.is_empty() {
+            select = select.group_by(&statement.groups.join(", "));
+        }
+
+        for (column, direction) in &statement.orders {
+            let direction = match direction {
+                Direction::Asc => "ASC",
+                Direction::Desc => "DESC",
+            };
+            select = select.order_by(&format!("{} {}", column, direction));
+        }
+
+        if let Some(limit) = statement.limit {
+            select = select.raw_after(sql::SelectClause::OrderBy, &format!("LIMIT {}", limit));
+        }
+        if let Some(offset) = statement.offset {
+            select = select.raw_after(sql::SelectClause::OrderBy, &format!("OFFSET {}", offset));
+        }
+
+        select.as_string()
     }
 
-    fn build_update_sql<T>(_statement: UpdateStatement<T>) -> String
+    fn build_update_sql<T>(statement: UpdateStatement<T>) -> String
     where
         T: Entity,
     {
-        sql::Update::new().as_string()
+        let mut update = sql::Update::new().update(&T::table_name());
+        for (column, value) in statement.assignments() {
+            update = update.set(&format!("{} = {}", column, value));
+        }
+        for filters in conditions(statement.conditions()) {
+            update = update.where_clause(&filters.to_sql());
+        }
+        if statement.returning {
+            update = update.raw_after(sql::UpdateClause::Where, "RETURNING *");
+        }
+
+        update.as_string()
+    }
+}
+
+/// The conditions of a write, the statements refuse to run when they can't
+/// be built, so the statement matches no row rather than every row then.
+fn conditions(conditions: Result<Vec<Filters>>) -> Vec<Filters> {
+    conditions.unwrap_or_else(|_| vec![Filters::Raw(String::from("1 = 0"))])
+}
+
+#[cfg(test)]
+mod test {
+    use omi::prelude::*;
+
+    use super::Builder;
+    use crate as omi;
+    use crate::order::Direction::{Asc, Desc};
+    use crate::statement::{DeleteStatement, Filters, SelectStatement, Statement};
+
+    #[derive(Debug, Default, Clone, Entity)]
+    #[entity(table = "reviews")]
+    struct Review {}
+
+    #[derive(Debug, Default, Clone, Entity)]
+    #[entity(table = "comments", soft_delete = "deleted_at")]
+    struct Comment {
+        #[column(primary, auto)]
+        id: u64,
+
+        deleted_at: Option<String>,
+    }
+
+    #[test]
+    fn test_build_select_filters() {
+        let mut stmt: SelectStatement<Review> = SelectStatement::new();
+        stmt.filter(("product_id", 1))
+            .filter(Filters::Raw("rating > 3".into()));
+
+        assert_eq!(
+            Builder::build(Statement::Select(stmt)),
+            "SELECT * FROM reviews WHERE product_id = 1 AND rating > 3"
+        );
+    }
+
+    #[test]
+    fn test_build_select_clauses() {
+        let mut stmt: SelectStatement<Review> = SelectStatement::new();
+        stmt.filter(("product_id", 1))
+            .group_by(["product_id".into(), "rating".into()])
+            .order_by([("rating".into(), Desc), ("id".into(), Asc)])
+            .limit(10)
+            .offset(20);
+
+        assert_eq!(
+            Builder::build(Statement::Select(stmt)),
+            "SELECT * FROM reviews WHERE product_id = 1 GROUP BY product_id, rating \
+             ORDER BY rating DESC, id ASC LIMIT 10 OFFSET 20"
+        );
+    }
+
+    #[test]
+    fn test_build_select_single() {
+        let stmt: SelectStatement<Review> = SelectStatement::new();
+
+        assert_eq!(
+            Builder::build(Statement::Select(stmt.limited(1))),
+            "SELECT * FROM reviews LIMIT 1"
+        );
+        assert_eq!(
+            Builder::build(Statement::Select(stmt.limited(2))),
+            "SELECT * FROM reviews LIMIT 2"
+        );
+    }
+
+    #[test]
+    fn test_build_soft_delete() {
+        let comment = Comment {
+            id: 7,
+            deleted_at: None,
+        };
+
+        assert_eq!(
+            Builder::build(Statement::Delete(DeleteStatement::new(comment.clone()))),
+            "UPDATE comments SET deleted_at = CURRENT_TIMESTAMP WHERE id = 7 AND deleted_at IS NULL"
+        );
+        assert_eq!(
+            Builder::build(Statement::Delete(DeleteStatement::restore(comment))),
+            "UPDATE comments SET deleted_at = NULL WHERE id = 7 AND deleted_at IS NOT NULL"
+        );
     }
 }
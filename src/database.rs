@@ -12,16 +12,161 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::thread;
+
+use crate::driver::Dialect;
+use crate::model::{Entity, Registry, Table};
+use crate::schema::{self, ValidationReport};
+use crate::transaction::{Transaction, TransactionOptions};
+use crate::{OmiError, Result};
+
+/// The outcome of a statement that writes rows.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ExecResult {
+    /// The number of rows inserted, updated or deleted.
+    pub rows_affected: u64,
+    /// The key generated for the inserted row, if the table has an auto
+    /// increment key.
+    pub last_insert_id: Option<u64>,
+}
+
 pub struct Database {
     dsn: String,
+    depth: usize,
 }
 
 impl Database {
     pub fn connect(dsn: String) -> Self {
-        Self { dsn }
+        Self { dsn, depth: 0 }
+    }
+
+    /// Begin a transaction on a dedicated connection, the transaction can be
+    /// passed wherever a `&Database` is accepted and rolls back on drop unless
+    /// it's committed. Inside a transaction, a savepoint is created instead.
+    pub fn begin(&self) -> Result<Transaction> {
+        self.begin_with(TransactionOptions::default())
+    }
+
+    /// Begin a transaction with the isolation level, access mode and
+    /// deferrable options.
+    pub fn begin_with(&self, options: TransactionOptions) -> Result<Transaction> {
+        Transaction::begin(self, &options)
+    }
+
+    /// Run the closure in a transaction, which is committed if it returns
+    /// `Ok` and rolled back if it returns `Err`. The transaction is retried
+    /// with backoff when it's aborted by a serialization failure or a
+    /// deadlock, so the closure may be called more than once. Inside a
+    /// transaction it runs in a savepoint, and the failure is left to the
+    /// outermost transaction to retry.
+    pub fn transaction<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnMut(&Transaction) -> Result<T>,
+    {
+        self.transaction_with(TransactionOptions::default(), f)
+    }
+
+    /// Like `transaction()`, with the options and the retry policy.
+    pub fn transaction_with<T, F>(&self, options: TransactionOptions, mut f: F) -> Result<T>
+    where
+        F: FnMut(&Transaction) -> Result<T>,
+    {
+        let mut attempt = 0;
+
+        loop {
+            let tx = self.begin_with(options)?;
+            let result = match f(&tx) {
+                Ok(value) => tx.commit().map(|_| value),
+                Err(error) => {
+                    let _ = tx.rollback();
+                    Err(error)
+                }
+            };
+
+            match result {
+                Err(OmiError::SerializationFailure(_))
+                    if !self.in_transaction() && attempt < options.retry.max_retries =>
+                {
+                    thread::sleep(options.retry.delay(attempt));
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Whether this connection belongs to a transaction.
+    pub fn in_transaction(&self) -> bool {
+        self.depth > 0
+    }
+
+    /// The nesting depth of the transactions and savepoints on this
+    /// connection, `0` outside a transaction.
+    pub fn depth(&self) -> usize {
+        self.depth
     }
 
-    pub fn execute<T>(&self, _sql: String) -> Result<Vec<T>, crate::OmiError> {
+    /// The connection of a transaction nested in this one, a dedicated
+    /// connection for the outermost transaction and the same connection for
+    /// a savepoint.
+    pub(crate) fn nested(&self) -> Self {
+        Self {
+            dsn: self.dsn.clone(),
+            depth: self.depth + 1,
+        }
+    }
+
+    /// The DSN this database was connected with.
+    pub fn dsn(&self) -> &str {
+        &self.dsn
+    }
+
+    /// The SQL dialect of this database, detected from the DSN scheme.
+    pub fn dialect(&self) -> Result<Dialect> {
+        Dialect::from_dsn(&self.dsn).ok_or_else(|| OmiError::InvalidDsn(self.dsn.clone()))
+    }
+
+    /// Validate the entity against the live table, so that a missing column,
+    /// an incompatible type or a nullability that disagrees with the `Option`
+    /// usage is reported at startup rather than at the first query.
+    pub fn validate<T: Entity>(&self) -> Result<()> {
+        self.validate_tables(&[T::meta().table])
+    }
+
+    /// Validate all entities of the registry against the live tables.
+    pub fn validate_all(&self, registry: &Registry) -> Result<()> {
+        self.validate_tables(&registry.tables())
+    }
+
+    fn validate_tables(&self, tables: &[Table]) -> Result<()> {
+        let dialect = self.dialect()?;
+        let live = schema::tables(self)?;
+
+        let report = ValidationReport {
+            mismatches: tables
+                .iter()
+                .flat_map(|table| schema::validate(table, &live, dialect))
+                .collect(),
+        };
+
+        match report.is_ok() {
+            true => Ok(()),
+            false => Err(OmiError::SchemaMismatch(report)),
+        }
+    }
+
+    pub fn execute<T>(&self, _sql: String) -> Result<Vec<T>> {
         Ok(vec![])
     }
+
+    /// Execute a statement that writes rows, returning the number of affected
+    /// rows and the generated key.
+    pub fn exec(&self, _sql: String) -> Result<ExecResult> {
+        Ok(ExecResult::default())
+    }
+
+    /// Execute a statement that returns no rows.
+    pub(crate) fn run(&self, sql: &str) -> Result<()> {
+        self.execute::<()>(sql.to_owned()).map(|_| ())
+    }
 }
@@ -14,6 +14,9 @@
 
 use crate::OmiError;
 
+mod dialect;
+pub use self::dialect::Dialect;
+
 #[cfg(feature = "mysql")]
 mod mysql;
 
@@ -1,7 +1,283 @@
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::schema::ValidationReport;
+use crate::validation::ValidationErrors;
+
 pub type Result<T, E = OmiError> = std::result::Result<T, E>;
 
 #[derive(Debug, PartialEq)]
 pub enum OmiError {
     DatabaseError,
     NotFoundError,
+    /// The entities don't match the live database schema.
+    SchemaMismatch(ValidationReport),
+    /// The relation doesn't exist on the entity or doesn't support the operation.
+    InvalidRelation(String),
+    /// The transaction was aborted by a serialization failure or a deadlock,
+    /// and can be retried.
+    SerializationFailure(DriverError),
+    /// The entity of the table was changed or deleted since it was loaded, as
+    /// no row matched its version.
+    StaleEntity(String),
+    /// The entity of the table has no primary key value, so no row can be
+    /// matched to write it.
+    MissingKey(String),
+    /// More than one row of the table matched a query for exactly one.
+    MultipleRows(String),
+    /// The entity failed its field validators, with the messages of each field.
+    Validation(ValidationErrors),
+    /// The connection to the database failed or was lost.
+    Connection(DriverError),
+    /// The statement or the wait for a lock timed out.
+    Timeout(DriverError),
+    /// No connection of the pool became free in time.
+    PoolExhausted(DriverError),
+    /// A row with the same value of the unique constraint already exists.
+    UniqueViolation {
+        constraint: Option<String>,
+        column: Option<String>,
+        error: DriverError,
+    },
+    /// The row references a missing row, or is referenced by another row.
+    ForeignKeyViolation {
+        constraint: Option<String>,
+        error: DriverError,
+    },
+    /// NULL was written to a NOT NULL column.
+    NotNullViolation {
+        column: Option<String>,
+        error: DriverError,
+    },
+    /// The row failed a CHECK constraint.
+    CheckViolation {
+        constraint: Option<String>,
+        error: DriverError,
+    },
+    /// The value of the column doesn't fit the field it's loaded into.
+    Decode {
+        column: String,
+        error: DriverError,
+    },
+    /// The statement failed for another reason, such as a syntax error.
+    Query(DriverError),
+    /// The DSN is malformed or its scheme is not supported.
+    InvalidDsn(String),
+}
+
+impl OmiError {
+    /// The error reported by the driver, if the error came from the database.
+    pub fn driver_error(&self) -> Option<&DriverError> {
+        match self {
+            OmiError::SerializationFailure(error)
+            | OmiError::Connection(error)
+            | OmiError::Timeout(error)
+            | OmiError::PoolExhausted(error)
+            | OmiError::UniqueViolation { error, .. }
+            | OmiError::ForeignKeyViolation { error, .. }
+            | OmiError::NotNullViolation { error, .. }
+            | OmiError::CheckViolation { error, .. }
+            | OmiError::Decode { error, .. }
+            | OmiError::Query(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// The SQL of the statement that failed.
+    pub fn sql(&self) -> Option<&str> {
+        self.driver_error().and_then(|error| error.sql())
+    }
+}
+
+impl fmt::Display for OmiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = |name: &Option<String>| name.as_deref().unwrap_or("unknown").to_owned();
+
+        match self {
+            OmiError::DatabaseError => write!(f, "database error"),
+            OmiError::NotFoundError => write!(f, "no row found"),
+            OmiError::SchemaMismatch(report) => write!(f, "{}", report),
+            OmiError::InvalidRelation(relation) => write!(f, "invalid relation `{}`", relation),
+            OmiError::SerializationFailure(error) => write!(f, "serialization failure: {}", error),
+            OmiError::StaleEntity(table) => {
+                write!(
+                    f,
+                    "stale entity of `{}`, the row was changed or deleted",
+                    table
+                )
+            }
+            OmiError::MissingKey(table) => {
+                write!(f, "entity of `{}` has no primary key value", table)
+            }
+            OmiError::MultipleRows(table) => write!(f, "more than one row of `{}` found", table),
+            OmiError::Validation(errors) => write!(f, "validation failed: {}", errors),
+            OmiError::Connection(error) => write!(f, "connection failed: {}", error),
+            OmiError::Timeout(error) => write!(f, "timed out: {}", error),
+            OmiError::PoolExhausted(error) => write!(f, "connection pool exhausted: {}", error),
+            OmiError::UniqueViolation {
+                constraint,
+                column,
+                error,
+            } => write!(
+                f,
+                "unique violation of constraint `{}` on column `{}`: {}",
+                name(constraint),
+                name(column),
+                error
+            ),
+            OmiError::ForeignKeyViolation { constraint, error } => write!(
+                f,
+                "foreign key violation of constraint `{}`: {}",
+                name(constraint),
+                error
+            ),
+            OmiError::NotNullViolation { column, error } => {
+                write!(
+                    f,
+                    "not null violation on column `{}`: {}",
+                    name(column),
+                    error
+                )
+            }
+            OmiError::CheckViolation { constraint, error } => write!(
+                f,
+                "check violation of constraint `{}`: {}",
+                name(constraint),
+                error
+            ),
+            OmiError::Decode { column, error } => {
+                write!(f, "failed to decode column `{}`: {}", column, error)
+            }
+            OmiError::Query(error) => write!(f, "query failed: {}", error),
+            OmiError::InvalidDsn(dsn) => write!(f, "invalid DSN `{}`", dsn),
+        }
+    }
+}
+
+impl Error for OmiError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.driver_error()
+            .map(|error| error as &(dyn Error + 'static))
+    }
+}
+
+/// The error reported by the database driver, with the SQL of the statement
+/// and the native error of the driver as its source.
+#[derive(Debug, Clone)]
+pub struct DriverError {
+    // boxed to keep `Result<T, OmiError>` small
+    detail: Box<Detail>,
+}
+
+#[derive(Debug, Clone)]
+struct Detail {
+    sql: Option<String>,
+    code: Option<String>,
+    message: String,
+    source: Option<Arc<dyn Error + Send + Sync>>,
+}
+
+impl DriverError {
+    pub fn new(message: impl Into<String>) -> Self {
+        let detail = Detail {
+            sql: None,
+            code: None,
+            message: message.into(),
+            source: None,
+        };
+
+        Self {
+            detail: Box::new(detail),
+        }
+    }
+
+    pub fn with_sql(mut self, sql: impl Into<String>) -> Self {
+        self.detail.sql = Some(sql.into());
+        self
+    }
+
+    pub fn with_code(mut self, code: impl Into<String>) -> Self {
+        self.detail.code = Some(code.into());
+        self
+    }
+
+    /// Keep the native error of the driver as the source.
+    pub fn with_source(mut self, source: impl Error + Send + Sync + 'static) -> Self {
+        self.detail.source = Some(Arc::new(source));
+        self
+    }
+
+    /// The SQL of the statement, `None` if the error is not about one.
+    pub fn sql(&self) -> Option<&str> {
+        self.detail.sql.as_deref()
+    }
+
+    /// The error code of the backend, such as `1062` or `23505`.
+    pub fn code(&self) -> Option<&str> {
+        self.detail.code.as_deref()
+    }
+
+    pub fn message(&self) -> &str {
+        &self.detail.message
+    }
+}
+
+/// Driver errors are equal if they have the same SQL, code and message.
+impl PartialEq for DriverError {
+    fn eq(&self, other: &Self) -> bool {
+        self.sql() == other.sql()
+            && self.code() == other.code()
+            && self.message() == other.message()
+    }
+}
+
+impl fmt::Display for DriverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.code() {
+            Some(code) => write!(f, "{} ({})", self.message(), code),
+            None => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+impl Error for DriverError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        let source = self.detail.source.as_deref()?;
+        Some(source as &(dyn Error + 'static))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::error::Error;
+    use std::io;
+
+    use super::{DriverError, OmiError};
+
+    #[test]
+    fn test_error_source() {
+        let driver = DriverError::new("Duplicate entry 'jane' for key 'name'")
+            .with_code("1062")
+            .with_sql("INSERT INTO users (name) VALUES ('jane')")
+            .with_source(io::Error::new(io::ErrorKind::Other, "server error"));
+        let error = OmiError::UniqueViolation {
+            constraint: Some("name".into()),
+            column: None,
+            error: driver,
+        };
+
+        assert_eq!(
+            error.to_string(),
+            "unique violation of constraint `name` on column `unknown`: Duplicate entry 'jane' for key 'name' (1062)"
+        );
+        assert_eq!(
+            error.sql(),
+            Some("INSERT INTO users (name) VALUES ('jane')")
+        );
+
+        let source = error.source().unwrap();
+        assert_eq!(source.source().unwrap().to_string(), "server error");
+        assert!(OmiError::NotFoundError.source().is_none());
+    }
 }
@@ -14,6 +14,7 @@
 
 pub mod model;
 pub mod operations;
+pub mod schema;
 pub mod statement;
 pub use operations::raw;
 
@@ -28,8 +29,8 @@ mod builder;
 pub mod prelude {
     pub use omi_macros::{Creatable, Deletable, Entity, Queryable, Updatable};
 
-    pub use crate::model::Entity;
-    pub use crate::operations::{Creatable, Deletable, Queryable, Updatable};
+    pub use crate::model::{Entity, EntityHooks};
+    pub use crate::operations::{Attachable, Creatable, Deletable, Queryable, Updatable};
 }
 
 pub mod order {
@@ -42,3 +43,4 @@ pub mod order {
 
 pub mod driver;
 pub mod transaction;
+pub mod validation;
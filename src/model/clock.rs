@@ -0,0 +1,91 @@
+// Copyright (c) The Amphitheatre Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::Value;
+
+/// The clock that fills the `created_at` and `updated_at` columns.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Clock {
+    /// The current time of the database, `CURRENT_TIMESTAMP`.
+    #[default]
+    Database,
+    /// The current time of the application, in UTC.
+    Application,
+}
+
+impl Clock {
+    /// The current time as SQL.
+    pub fn now_sql(&self) -> String {
+        match self {
+            Clock::Database => String::from("CURRENT_TIMESTAMP"),
+            Clock::Application => {
+                let secs = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |duration| duration.as_secs());
+                Value::Text(format_utc(secs)).to_sql()
+            }
+        }
+    }
+}
+
+/// Format the seconds since the Unix epoch as `YYYY-MM-DD HH:MM:SS` in UTC,
+/// which every dialect accepts for a timestamp or datetime column.
+pub fn format_utc(secs: u64) -> String {
+    // The civil date of the days since the epoch, in eras of 400 years.
+    let days = (secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    let time = secs % 86_400;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::{format_utc, Clock};
+
+    #[test]
+    fn test_format_utc() {
+        assert_eq!(format_utc(0), "1970-01-01 00:00:00");
+        assert_eq!(format_utc(951_782_400), "2000-02-29 00:00:00");
+        assert_eq!(format_utc(1_700_000_000), "2023-11-14 22:13:20");
+    }
+
+    #[test]
+    fn test_clock_now_sql() {
+        assert_eq!(Clock::Database.now_sql(), "CURRENT_TIMESTAMP");
+        assert!(Clock::Application.now_sql().starts_with("'20"));
+    }
+}
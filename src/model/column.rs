@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Column {
     /// The name of the column.
     pub name: String,
@@ -32,7 +32,7 @@ pub struct Column {
     pub index: bool,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum DataKind {
     /// An integer data type.
     Integer(Option<usize>),
@@ -12,8 +12,65 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::Meta;
+use super::{EntityHooks, Meta, Snapshot, Value};
+use crate::statement::Filters;
+use crate::validation::ValidationErrors;
+use crate::{Database, Result};
 
-pub trait Entity {
+pub trait Entity: EntityHooks {
     fn meta() -> Meta;
+
+    /// The table name of the entity, which is cheaper than building the meta
+    /// and safe to call from the meta of a related entity.
+    fn table_name() -> String {
+        Self::meta().table.name
+    }
+
+    /// The value of the column, `None` if the entity has no such column.
+    fn value(&self, _column: &str) -> Option<Value> {
+        None
+    }
+
+    /// Set the value of the column, `false` if the entity has no such column
+    /// or the value doesn't fit the field.
+    fn set_value(&mut self, _column: &str, _value: Value) -> bool {
+        false
+    }
+
+    /// The snapshot of the column values as they were loaded, `None` if the
+    /// entity has no `Snapshot` field.
+    fn snapshot(&self) -> Option<&Snapshot> {
+        None
+    }
+
+    /// Take the snapshot of the column values after the entity is loaded.
+    fn take_snapshot(&mut self) {}
+
+    /// Check the `#[validate(...)]` validators of the fields, recording the
+    /// failures in the errors.
+    fn validate(&self, _errors: &mut ValidationErrors) {}
+
+    /// The filter added to every query, bulk update and delete of the entity,
+    /// unless the statement is `unscoped()`.
+    fn default_scope() -> Option<Filters>
+    where
+        Self: Sized,
+    {
+        None
+    }
+
+    /// Load the relations of the paths onto the entities, a nested path such
+    /// as `reviews.author` loads the authors onto the loaded reviews.
+    fn load_relations(_entities: &mut [Self], _paths: &[String], _db: &Database) -> Result<()>
+    where
+        Self: Sized,
+    {
+        Ok(())
+    }
+
+    /// Save the relations marked with `cascade = "save"` after the entity has
+    /// been inserted, writing its key into the related entities.
+    fn save_relations(&mut self, _db: &Database) -> Result<()> {
+        Ok(())
+    }
 }
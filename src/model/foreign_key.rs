@@ -0,0 +1,91 @@
+// Copyright (c) The Amphitheatre Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForeignKey {
+    /// The name of the constraint.
+    pub name: String,
+    /// The local columns that reference the other table.
+    pub columns: Vec<String>,
+    /// The name of the referenced table.
+    pub referenced_table: String,
+    /// The referenced columns of the other table.
+    pub referenced_columns: Vec<String>,
+    /// The action when the referenced row is deleted.
+    pub on_delete: ReferentialAction,
+    /// The action when the referenced key is updated.
+    pub on_update: ReferentialAction,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ReferentialAction {
+    /// Reject the change at the end of the statement, the database default.
+    #[default]
+    NoAction,
+    /// Reject the change immediately.
+    Restrict,
+    /// Delete or update the referencing rows as well.
+    Cascade,
+    /// Set the referencing columns to NULL.
+    SetNull,
+    /// Set the referencing columns to their default values.
+    SetDefault,
+}
+
+impl ReferentialAction {
+    /// Parse an action such as `cascade`, `set_null` or `SET NULL`.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_uppercase().replace('_', " ").as_str() {
+            "NO ACTION" => Some(ReferentialAction::NoAction),
+            "RESTRICT" => Some(ReferentialAction::Restrict),
+            "CASCADE" => Some(ReferentialAction::Cascade),
+            "SET NULL" => Some(ReferentialAction::SetNull),
+            "SET DEFAULT" => Some(ReferentialAction::SetDefault),
+            _ => None,
+        }
+    }
+
+    /// The SQL keywords of the action.
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            ReferentialAction::NoAction => "NO ACTION",
+            ReferentialAction::Restrict => "RESTRICT",
+            ReferentialAction::Cascade => "CASCADE",
+            ReferentialAction::SetNull => "SET NULL",
+            ReferentialAction::SetDefault => "SET DEFAULT",
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ReferentialAction;
+
+    #[test]
+    fn test_parse_referential_action() {
+        assert_eq!(
+            ReferentialAction::parse("cascade"),
+            Some(ReferentialAction::Cascade)
+        );
+        assert_eq!(
+            ReferentialAction::parse("set_null"),
+            Some(ReferentialAction::SetNull)
+        );
+        assert_eq!(
+            ReferentialAction::parse("SET DEFAULT"),
+            Some(ReferentialAction::SetDefault)
+        );
+        assert_eq!(ReferentialAction::parse("nothing"), None);
+    }
+}
@@ -0,0 +1,53 @@
+// Copyright (c) The Amphitheatre Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{Database, Result};
+
+/// Callbacks around the persistence of an entity, e.g. to generate a slug or
+/// normalise fields before they are written.
+///
+/// `#[derive(Entity)]` implements it without callbacks, unless the entity is
+/// marked with `#[entity(hooks)]`, then implement it yourself. The callbacks
+/// of a write run in the same transaction as the statement, and an error
+/// rolls it back.
+pub trait EntityHooks {
+    fn before_insert(&mut self, _db: &Database) -> Result<()> {
+        Ok(())
+    }
+
+    fn after_insert(&mut self, _db: &Database) -> Result<()> {
+        Ok(())
+    }
+
+    fn before_update(&mut self, _db: &Database) -> Result<()> {
+        Ok(())
+    }
+
+    fn after_update(&mut self, _db: &Database) -> Result<()> {
+        Ok(())
+    }
+
+    fn before_delete(&mut self, _db: &Database) -> Result<()> {
+        Ok(())
+    }
+
+    fn after_delete(&mut self, _db: &Database) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called for each entity fetched by a query.
+    fn after_load(&mut self, _db: &Database) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,35 @@
+// Copyright (c) The Amphitheatre Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// A table level index, which may span several columns and may be partial.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Index {
+    /// The name of the index.
+    pub name: String,
+    /// The indexed columns, in order.
+    pub columns: Vec<String>,
+    /// Whether the index is unique.
+    pub unique: bool,
+    /// The predicate of a partial index, the `WHERE` clause without keyword.
+    pub condition: Option<String>,
+}
+
+/// A table level `CHECK` constraint.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Check {
+    /// The name of the constraint.
+    pub name: String,
+    /// The boolean SQL expression to check.
+    pub expression: String,
+}
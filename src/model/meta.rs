@@ -1,8 +1,31 @@
-use super::Table;
+use super::{Clock, Relation, Table};
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct Meta {
     pub table: Table,
+    pub relations: Vec<Relation>,
+    /// The version column checked and bumped by updates and deletes, for
+    /// optimistic locking.
+    pub version: Option<String>,
+    /// The column set to the current time on insert.
+    pub created_at: Option<String>,
+    /// The column set to the current time on insert and update.
+    pub updated_at: Option<String>,
+    /// The clock of the `created_at` and `updated_at` columns.
+    pub clock: Clock,
+    /// The nullable column set to the current time instead of deleting the
+    /// row, the rows where it's set are left out of queries.
+    pub soft_delete: Option<String>,
+    /// Whether the entity implements its own `EntityHooks`, so that its
+    /// writes run in a transaction with the callbacks.
+    pub hooks: bool,
+}
+
+impl Meta {
+    /// Find a relation by the name of its field.
+    pub fn relation(&self, name: &str) -> Option<&Relation> {
+        self.relations.iter().find(|r| r.name == name)
+    }
 }
 
 #[cfg(test)]
@@ -10,13 +33,72 @@ mod test {
     use omi::prelude::*;
 
     use crate as omi;
+    use crate::model::RelationKind;
 
     #[derive(Debug, Default, Clone, Entity)]
     #[entity(table = "products")]
-    struct Product {}
+    struct Product {
+        #[column(primary, auto)]
+        id: u64,
+
+        #[relation(has_many)]
+        reviews: Vec<Review>,
+    }
+
+    #[derive(Debug, Default, Clone, Entity)]
+    #[entity(table = "reviews")]
+    struct Review {
+        #[column(type = "timestamp")]
+        deleted_at: Option<String>,
+
+        product_id: u64,
+
+        #[relation(belongs_to, local_key = "product_id", foreign_key = "id")]
+        product: Product,
+    }
 
     #[test]
     fn test_entity_meta() {
         assert_eq!(Product::meta().table.name, "products");
     }
+
+    #[test]
+    fn test_entity_meta_columns() {
+        let columns = Review::meta().table.columns;
+
+        assert_eq!(columns.len(), 2);
+        assert!(columns[0].null);
+        assert!(!columns[1].null);
+    }
+
+    #[test]
+    fn test_entity_meta_foreign_keys() {
+        let foreign_keys = Review::meta().table.foreign_keys;
+
+        assert_eq!(foreign_keys.len(), 1);
+        assert_eq!(foreign_keys[0].name, "fk_reviews_product_id");
+        assert_eq!(foreign_keys[0].columns, vec!["product_id"]);
+        assert_eq!(foreign_keys[0].referenced_table, "products");
+        assert_eq!(foreign_keys[0].referenced_columns, vec!["id"]);
+    }
+
+    #[test]
+    fn test_entity_meta_relations() {
+        let meta = Product::meta();
+        let reviews = meta.relation("reviews").unwrap();
+
+        assert_eq!(meta.table.columns.len(), 1);
+        assert_eq!(reviews.kind, RelationKind::HasMany);
+        assert_eq!(reviews.target, "reviews");
+        assert_eq!(reviews.local_key, "id");
+        assert_eq!(reviews.foreign_key, "product_id");
+
+        let meta = Review::meta();
+        let product = meta.relation("product").unwrap();
+
+        assert_eq!(product.kind, RelationKind::BelongsTo);
+        assert_eq!(product.target, "products");
+        assert_eq!(product.local_key, "product_id");
+        assert_eq!(product.foreign_key, "id");
+    }
 }
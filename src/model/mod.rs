@@ -12,14 +12,41 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod clock;
+pub use self::clock::{format_utc, Clock};
+
 mod entity;
 pub use self::entity::Entity;
 
+mod foreign_key;
+pub use self::foreign_key::*;
+
+mod hooks;
+pub use self::hooks::EntityHooks;
+
+mod index;
+pub use self::index::*;
+
+mod lazy;
+pub use self::lazy::Lazy;
+
 mod meta;
 pub use self::meta::Meta;
 
+mod registry;
+pub use self::registry::Registry;
+
+mod relation;
+pub use self::relation::*;
+
+mod snapshot;
+pub use self::snapshot::Snapshot;
+
 mod table;
 pub use self::table::*;
 
 mod column;
 pub use self::column::*;
+
+mod value;
+pub use self::value::*;
@@ -0,0 +1,71 @@
+// Copyright (c) The Amphitheatre Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{Entity, Meta, Table};
+
+/// A collection of entities, used to work on all entities of an application
+/// at once, such as validating them against the database.
+#[derive(Debug, Default, Clone)]
+pub struct Registry {
+    metas: Vec<Meta>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the entity to the registry
+    pub fn register<T: Entity>(&mut self) -> &mut Self {
+        self.metas.push(T::meta());
+        self
+    }
+
+    /// The meta of the registered entities, in the order of registration
+    pub fn metas(&self) -> &[Meta] {
+        &self.metas
+    }
+
+    /// The tables of the registered entities, in the order of registration
+    pub fn tables(&self) -> Vec<Table> {
+        self.metas.iter().map(|meta| meta.table.clone()).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use omi::prelude::*;
+
+    use super::Registry;
+    use crate as omi;
+
+    #[derive(Debug, Default, Clone, Entity)]
+    #[entity(table = "products")]
+    struct Product {}
+
+    #[derive(Debug, Default, Clone, Entity)]
+    #[entity(table = "reviews")]
+    struct Review {}
+
+    #[test]
+    fn test_registry_register() {
+        let tables = Registry::new()
+            .register::<Product>()
+            .register::<Review>()
+            .tables();
+        let names: Vec<&str> = tables.iter().map(|t| t.name.as_str()).collect();
+
+        assert_eq!(names, vec!["products", "reviews"]);
+    }
+}
@@ -0,0 +1,76 @@
+// Copyright (c) The Amphitheatre Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// The kind of a relation between two entities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelationKind {
+    /// This entity holds the key of a single target entity.
+    BelongsTo,
+    /// A single target entity holds the key of this entity.
+    HasOne,
+    /// Many target entities hold the key of this entity.
+    HasMany,
+    /// Entities related through the rows of a join table.
+    ManyToMany,
+    /// This entity holds the key and the type of a single target entity,
+    /// which is one of several entity types.
+    MorphTo,
+}
+
+/// The join table of a many to many relation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JoinTable {
+    /// The name of the join table.
+    pub table: String,
+    /// The column of the join table that references the local key.
+    pub local_key: String,
+    /// The column of the join table that references the foreign key.
+    pub foreign_key: String,
+}
+
+/// The type discriminator of a polymorphic relation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Morph {
+    /// The column that holds the table name of the target entity.
+    pub type_column: String,
+    /// The table names of the possible target entities.
+    pub types: Vec<String>,
+}
+
+/// A relation field of an entity, which is not mapped to a column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Relation {
+    /// The name of the relation field.
+    pub name: String,
+    /// The kind of the relation.
+    pub kind: RelationKind,
+    /// The table name of the target entity, empty for a polymorphic relation
+    /// whose targets are in `morph`.
+    pub target: String,
+    /// The key column on the table of the target entity.
+    pub foreign_key: String,
+    /// The key column on the table of this entity, matched against the
+    /// foreign key of the target.
+    pub local_key: String,
+    /// The join table of a many to many relation.
+    pub through: Option<JoinTable>,
+    /// The type discriminator of a polymorphic relation, on this table for
+    /// `MorphTo` and on the target table for the `as` side.
+    pub morph: Option<Morph>,
+    /// Whether the related entities are inserted with this entity.
+    pub cascade: bool,
+    /// Whether the relation is a `Lazy` field, which is only loaded with the
+    /// parent when it's included explicitly.
+    pub lazy: bool,
+}
@@ -0,0 +1,127 @@
+// Copyright (c) The Amphitheatre Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::HashMap;
+
+use super::{Entity, Value};
+
+/// The column values of an entity as they were loaded, so that updating it
+/// only writes the changed columns. Declare a field of this type on the
+/// entity to track it, it's taken when the entity is fetched.
+///
+/// Snapshots don't take part in the equality of entities.
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot {
+    values: Option<HashMap<String, Value>>,
+}
+
+impl Snapshot {
+    /// Take the snapshot of the column values of the entity.
+    pub fn of<T: Entity>(entity: &T) -> Self {
+        let values = T::meta()
+            .table
+            .columns
+            .iter()
+            .filter_map(|column| Some((column.name.clone(), entity.value(&column.name)?)))
+            .collect();
+
+        Self {
+            values: Some(values),
+        }
+    }
+
+    /// Whether the snapshot was taken, it's not for entities that were not
+    /// loaded from the database.
+    pub fn is_taken(&self) -> bool {
+        self.values.is_some()
+    }
+
+    /// The loaded value of the column.
+    pub fn get(&self, column: &str) -> Option<&Value> {
+        self.values.as_ref()?.get(column)
+    }
+
+    /// The columns whose value differs from the snapshot, with their current
+    /// values. The primary key is left out, and every other column is changed
+    /// if the snapshot was not taken.
+    pub fn changes<T: Entity>(&self, entity: &T) -> Vec<(String, Value)> {
+        T::meta()
+            .table
+            .columns
+            .iter()
+            .filter(|column| !column.primary)
+            .filter_map(|column| {
+                let value = entity.value(&column.name)?;
+                match self.get(&column.name) {
+                    Some(original) if *original == value => None,
+                    _ => Some((column.name.clone(), value)),
+                }
+            })
+            .collect()
+    }
+}
+
+impl PartialEq for Snapshot {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use omi::prelude::*;
+
+    use super::Snapshot;
+    use crate as omi;
+    use crate::model::ToValue;
+
+    #[derive(Debug, Default, Clone, PartialEq, Entity)]
+    #[entity(table = "products")]
+    struct Product {
+        #[column(primary, auto)]
+        id: u64,
+
+        title: String,
+
+        price: f64,
+
+        snapshot: Snapshot,
+    }
+
+    #[test]
+    fn test_snapshot_changes() {
+        let mut product = Product {
+            id: 1,
+            title: "Rust".into(),
+            price: 9.9,
+            ..Default::default()
+        };
+        assert_eq!(Snapshot::default().changes(&product).len(), 2);
+
+        product.take_snapshot();
+        assert!(product.snapshot.is_taken());
+        assert!(product.snapshot.changes(&product).is_empty());
+
+        product.price = 19.9;
+        assert_eq!(
+            product.snapshot.changes(&product),
+            vec![("price".to_owned(), 19.9.to_value())]
+        );
+        assert!(Product::meta()
+            .table
+            .columns
+            .iter()
+            .all(|c| c.name != "snapshot"));
+    }
+}
@@ -12,12 +12,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::Column;
+use super::{Check, Column, ForeignKey, Index};
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct Table {
     /// The name of the table.
     pub name: String,
     /// The columns in the table.
     pub columns: Vec<Column>,
+    /// The foreign key constraints of the table.
+    pub foreign_keys: Vec<ForeignKey>,
+    /// The table level indexes, such as composite and partial indexes.
+    pub indexes: Vec<Index>,
+    /// The check constraints of the table.
+    pub checks: Vec<Check>,
 }
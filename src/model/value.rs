@@ -0,0 +1,245 @@
+// Copyright (c) The Amphitheatre Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// A column value of an entity, used to build filters and to match the keys
+/// of related entities.
+#[derive(Debug, Clone)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Float(f64),
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl Value {
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    /// Render the value as a SQL literal.
+    pub fn to_sql(&self) -> String {
+        match self {
+            Value::Null => String::from("NULL"),
+            Value::Bool(true) => String::from("TRUE"),
+            Value::Bool(false) => String::from("FALSE"),
+            Value::Int(value) => value.to_string(),
+            Value::UInt(value) => value.to_string(),
+            Value::Float(value) => value.to_string(),
+            Value::Text(value) => format!("'{}'", value.replace('\'', "''")),
+            Value::Bytes(value) => format!(
+                "X'{}'",
+                value
+                    .iter()
+                    .map(|b| format!("{:02X}", b))
+                    .collect::<String>()
+            ),
+        }
+    }
+}
+
+/// Integers compare by value regardless of signedness, so that an `i64`
+/// foreign key matches an `u64` primary key.
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Null, Value::Null) => true,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::UInt(a), Value::UInt(b)) => a == b,
+            (Value::Int(a), Value::UInt(b)) | (Value::UInt(b), Value::Int(a)) => {
+                *a >= 0 && *a as u64 == *b
+            }
+            (Value::Float(a), Value::Float(b)) => a == b,
+            (Value::Text(a), Value::Text(b)) => a == b,
+            (Value::Bytes(a), Value::Bytes(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// Convert a field into a column value, implement it for custom field types.
+pub trait ToValue {
+    fn to_value(&self) -> Value;
+}
+
+macro_rules! impl_to_value {
+    ($variant:ident, $target:ty, $($ty:ty),+) => {
+        $(
+            impl ToValue for $ty {
+                fn to_value(&self) -> Value {
+                    Value::$variant(*self as $target)
+                }
+            }
+        )+
+    };
+}
+
+impl_to_value!(Int, i64, i8, i16, i32, i64, isize);
+impl_to_value!(UInt, u64, u8, u16, u32, u64, usize);
+impl_to_value!(Float, f64, f32, f64);
+
+impl ToValue for bool {
+    fn to_value(&self) -> Value {
+        Value::Bool(*self)
+    }
+}
+
+impl ToValue for String {
+    fn to_value(&self) -> Value {
+        Value::Text(self.clone())
+    }
+}
+
+impl ToValue for &str {
+    fn to_value(&self) -> Value {
+        Value::Text((*self).to_owned())
+    }
+}
+
+impl ToValue for Vec<u8> {
+    fn to_value(&self) -> Value {
+        Value::Bytes(self.clone())
+    }
+}
+
+impl ToValue for Value {
+    fn to_value(&self) -> Value {
+        self.clone()
+    }
+}
+
+impl<T: ToValue> ToValue for Option<T> {
+    fn to_value(&self) -> Value {
+        match self {
+            Some(value) => value.to_value(),
+            None => Value::Null,
+        }
+    }
+}
+
+/// Convert a column value back into a field, `None` if the value doesn't fit
+/// the field type.
+pub trait FromValue: Sized {
+    fn from_value(value: Value) -> Option<Self>;
+}
+
+macro_rules! impl_from_int_value {
+    ($($ty:ty),+) => {
+        $(
+            impl FromValue for $ty {
+                fn from_value(value: Value) -> Option<Self> {
+                    match value {
+                        Value::Int(value) => <$ty>::try_from(value).ok(),
+                        Value::UInt(value) => <$ty>::try_from(value).ok(),
+                        _ => None,
+                    }
+                }
+            }
+        )+
+    };
+}
+
+impl_from_int_value!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl FromValue for f32 {
+    fn from_value(value: Value) -> Option<Self> {
+        f64::from_value(value).map(|value| value as f32)
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Float(value) => Some(value),
+            Value::Int(value) => Some(value as f64),
+            Value::UInt(value) => Some(value as f64),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Bool(value) => Some(value),
+            Value::Int(value) => Some(value != 0),
+            Value::UInt(value) => Some(value != 0),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Text(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for Vec<u8> {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Bytes(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue for Value {
+    fn from_value(value: Value) -> Option<Self> {
+        Some(value)
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: Value) -> Option<Self> {
+        match value {
+            Value::Null => Some(None),
+            value => T::from_value(value).map(Some),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FromValue, ToValue, Value};
+
+    #[test]
+    fn test_value_to_sql() {
+        assert_eq!(Some("it's").to_value().to_sql(), "'it''s'");
+        assert_eq!(None::<i32>.to_value().to_sql(), "NULL");
+        assert_eq!(true.to_value().to_sql(), "TRUE");
+        assert_eq!(vec![0u8, 255].to_value().to_sql(), "X'00FF'");
+    }
+
+    #[test]
+    fn test_value_eq() {
+        assert_eq!(5i64.to_value(), 5u64.to_value());
+        assert_ne!((-1i64).to_value(), u64::MAX.to_value());
+        assert_ne!(Value::Null, 0.to_value());
+    }
+
+    #[test]
+    fn test_value_from_value() {
+        assert_eq!(u64::from_value(Value::Int(7)), Some(7));
+        assert_eq!(u8::from_value(Value::Int(-1)), None);
+        assert_eq!(Option::<i32>::from_value(Value::Null), Some(None));
+        assert_eq!(String::from_value(Value::UInt(1)), None);
+    }
+}
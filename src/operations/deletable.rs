@@ -15,8 +15,19 @@
 use crate::statement::DeleteStatement;
 
 pub trait Deletable<T> {
-    /// Begin a delete session, which accepts an entity and returns a Statement instance
+    /// Begin a delete session, which accepts an entity and returns a Statement instance.
+    /// An entity with soft delete is marked as deleted instead of deleting the row.
     fn delete(entity: T) -> DeleteStatement<T>;
+
+    /// Delete the row of an entity with soft delete.
+    fn force_delete(entity: T) -> DeleteStatement<T>;
+
+    /// Restore an entity that was soft deleted.
+    fn restore(entity: T) -> DeleteStatement<T>;
+
+    /// Begin a bulk delete of the rows matching the filters of the statement,
+    /// within the default scope of the entity.
+    fn delete_all() -> DeleteStatement<T>;
 }
 
 #[cfg(test)]
@@ -32,6 +43,7 @@ mod test {
     #[test]
     fn test_deletable_delete() {
         let statement = Product::delete(Product::default());
-        assert_eq!(statement.entity, Product::default());
+        assert_eq!(statement.entity, Some(Product::default()));
+        assert_eq!(Product::delete_all().entity, None);
     }
 }
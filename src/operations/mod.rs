@@ -14,6 +14,9 @@
 
 //! Provides functions for database operations
 
+mod attachable;
+pub use attachable::*;
+
 mod creatable;
 pub use creatable::*;
 
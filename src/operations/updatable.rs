@@ -21,6 +21,8 @@ pub trait Updatable<T> {
     /// 1. Call with entity instance:
     /// Omi will automatically recognize the model of this instance and compare the
     /// differences, saving only the changed part of the fields to the database.
+    /// The differences are tracked for entities with a `Snapshot` field, which
+    /// is taken when they are loaded, other entities save all the fields.
     ///
     /// 2. Call without entity instance:
     /// It's mean you want to update the specified fields raw, this way, you need to
@@ -0,0 +1,438 @@
+// Copyright (c) The Amphitheatre Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::driver::Dialect;
+use crate::model::{Check, Column, DataKind, ForeignKey, Index, ReferentialAction, Table};
+
+/// The statements of a schema change, and the warnings about the parts of
+/// it the dialect can't make, which are left out of the statements.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Changes {
+    pub statements: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl Changes {
+    /// Add the statement, or the warning if the dialect can't make it.
+    pub fn push(&mut self, change: Result<String, String>) {
+        match change {
+            Ok(statement) => self.statements.push(statement),
+            Err(warning) => self.warnings.push(warning),
+        }
+    }
+
+    pub fn extend(&mut self, changes: Changes) {
+        self.statements.extend(changes.statements);
+        self.warnings.extend(changes.warnings);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.statements.is_empty() && self.warnings.is_empty()
+    }
+}
+
+/// Generate the statements that create the table and its indexes.
+pub fn create_table(table: &Table, dialect: Dialect) -> Changes {
+    let primaries: Vec<&Column> = table.columns.iter().filter(|c| c.primary).collect();
+    // SQLite only supports auto increment on an inline `INTEGER PRIMARY KEY`.
+    let inline_primary = dialect == Dialect::Sqlite && primaries.len() == 1 && primaries[0].auto;
+
+    let mut definitions: Vec<String> = table
+        .columns
+        .iter()
+        .map(|column| column_definition(column, dialect, inline_primary && column.primary))
+        .collect();
+
+    if !primaries.is_empty() && !inline_primary {
+        let names: Vec<&str> = primaries.iter().map(|c| c.name.as_str()).collect();
+        definitions.push(format!("PRIMARY KEY ({})", quote_all(&names, dialect)));
+    }
+
+    for column in table.columns.iter().filter(|c| c.unique && !c.primary) {
+        definitions.push(format!("UNIQUE ({})", dialect.quote(&column.name)));
+    }
+
+    for foreign_key in &table.foreign_keys {
+        definitions.push(foreign_key_definition(foreign_key, dialect));
+    }
+
+    for check in &table.checks {
+        definitions.push(check_definition(check, dialect));
+    }
+
+    let mut changes = Changes::default();
+    changes.statements.push(format!(
+        "CREATE TABLE {} (\n  {}\n)",
+        dialect.quote(&table.name),
+        definitions.join(",\n  ")
+    ));
+
+    for column in table
+        .columns
+        .iter()
+        .filter(|c| c.index && !c.unique && !c.primary)
+    {
+        changes
+            .statements
+            .push(create_index(table, column, dialect));
+    }
+
+    for index in &table.indexes {
+        changes.push(create_table_index(table, index, dialect));
+    }
+
+    changes
+}
+
+/// Generate the statement that drops the table.
+pub fn drop_table(name: &str, dialect: Dialect) -> String {
+    format!("DROP TABLE {}", dialect.quote(name))
+}
+
+/// Generate the statement that creates a single column index.
+pub fn create_index(table: &Table, column: &Column, dialect: Dialect) -> String {
+    format!(
+        "CREATE INDEX {} ON {} ({})",
+        dialect.quote(&format!("idx_{}_{}", table.name, column.name)),
+        dialect.quote(&table.name),
+        dialect.quote(&column.name)
+    )
+}
+
+/// Generate the statement that creates a table level index, or a warning on
+/// MySQL for a partial index, which it doesn't support.
+pub fn create_table_index(
+    table: &Table,
+    index: &Index,
+    dialect: Dialect,
+) -> Result<String, String> {
+    let columns: Vec<&str> = index.columns.iter().map(String::as_str).collect();
+    let mut statement = format!(
+        "CREATE {}INDEX {} ON {} ({})",
+        if index.unique { "UNIQUE " } else { "" },
+        dialect.quote(&index.name),
+        dialect.quote(&table.name),
+        quote_all(&columns, dialect)
+    );
+
+    if let Some(condition) = &index.condition {
+        if dialect == Dialect::MySQL {
+            return Err(format!(
+                "MySQL does not support partial indexes, index {} of {} is not created",
+                index.name, table.name
+            ));
+        }
+        statement.push_str(&format!(" WHERE {}", condition));
+    }
+
+    Ok(statement)
+}
+
+/// Generate the statement that drops a table level index.
+pub fn drop_table_index(table: &Table, index: &Index, dialect: Dialect) -> String {
+    match dialect {
+        Dialect::MySQL => format!(
+            "DROP INDEX {} ON {}",
+            dialect.quote(&index.name),
+            dialect.quote(&table.name)
+        ),
+        _ => format!("DROP INDEX {}", dialect.quote(&index.name)),
+    }
+}
+
+/// Generate the constraint definition used by `CREATE TABLE` and `ADD CONSTRAINT`.
+pub fn check_definition(check: &Check, dialect: Dialect) -> String {
+    format!(
+        "CONSTRAINT {} CHECK ({})",
+        dialect.quote(&check.name),
+        check.expression
+    )
+}
+
+/// Generate the constraint definition used by `CREATE TABLE` and `ADD CONSTRAINT`.
+pub fn foreign_key_definition(foreign_key: &ForeignKey, dialect: Dialect) -> String {
+    let columns: Vec<&str> = foreign_key.columns.iter().map(String::as_str).collect();
+    let referenced: Vec<&str> = foreign_key
+        .referenced_columns
+        .iter()
+        .map(String::as_str)
+        .collect();
+
+    let mut definition = format!(
+        "CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {} ({})",
+        dialect.quote(&foreign_key.name),
+        quote_all(&columns, dialect),
+        dialect.quote(&foreign_key.referenced_table),
+        quote_all(&referenced, dialect)
+    );
+
+    if foreign_key.on_delete != ReferentialAction::NoAction {
+        definition.push_str(&format!(" ON DELETE {}", foreign_key.on_delete.as_sql()));
+    }
+    if foreign_key.on_update != ReferentialAction::NoAction {
+        definition.push_str(&format!(" ON UPDATE {}", foreign_key.on_update.as_sql()));
+    }
+
+    definition
+}
+
+/// Generate the column definition used by `CREATE TABLE` and `ADD COLUMN`.
+pub fn column_definition(column: &Column, dialect: Dialect, inline_primary: bool) -> String {
+    let mut definition = format!(
+        "{} {}",
+        dialect.quote(&column.name),
+        column_type(column, dialect)
+    );
+
+    if inline_primary {
+        definition.push_str(" PRIMARY KEY AUTOINCREMENT");
+        return definition;
+    }
+
+    if !column.null {
+        definition.push_str(" NOT NULL");
+    }
+
+    if let Some(default) = &column.default {
+        definition.push_str(&format!(
+            " DEFAULT {}",
+            default_literal(&column.kind, default)
+        ));
+    }
+
+    if column.auto && dialect == Dialect::MySQL {
+        definition.push_str(" AUTO_INCREMENT");
+    }
+
+    definition
+}
+
+/// Map the column data kind to the type name of the dialect.
+pub fn column_type(column: &Column, dialect: Dialect) -> String {
+    if column.auto && dialect == Dialect::Postgres {
+        return String::from("BIGSERIAL");
+    }
+
+    kind_type(&column.kind, dialect)
+}
+
+/// Map a data kind to the type name of the dialect.
+pub fn kind_type(kind: &DataKind, dialect: Dialect) -> String {
+    match (kind, dialect) {
+        (DataKind::Integer(size), _) => {
+            let width = integer_width(*size, dialect);
+            let types = integer_types(dialect);
+            let (_, name) = types.iter().find(|(w, _)| *w == width).unwrap_or(&types[0]);
+            String::from(*name)
+        }
+        (
+            DataKind::Float {
+                max_digits: Some(digits),
+                decimal_places,
+            },
+            _,
+        ) => format!("DECIMAL({}, {})", digits, decimal_places.unwrap_or(0)),
+        (DataKind::Float { .. }, Dialect::MySQL) => String::from("DOUBLE"),
+        (DataKind::Float { .. }, Dialect::Postgres) => String::from("DOUBLE PRECISION"),
+        (DataKind::Float { .. }, Dialect::Sqlite) => String::from("REAL"),
+        (DataKind::Text(Some(size)), _) => format!("VARCHAR({})", size),
+        (DataKind::Text(None), _) => String::from("TEXT"),
+        (DataKind::Blob, Dialect::Postgres) => String::from("BYTEA"),
+        (DataKind::Blob, _) => String::from("BLOB"),
+        (DataKind::Timestamp, _) => String::from("TIMESTAMP"),
+        (DataKind::Date, _) => String::from("DATE"),
+        (DataKind::Time, _) => String::from("TIME"),
+        (DataKind::Datetime, Dialect::Postgres) => String::from("TIMESTAMP"),
+        (DataKind::Datetime, _) => String::from("DATETIME"),
+        (DataKind::Year, Dialect::MySQL) => String::from("YEAR"),
+        (DataKind::Year, Dialect::Postgres) => String::from("SMALLINT"),
+        (DataKind::Year, Dialect::Sqlite) => String::from("INTEGER"),
+        (DataKind::Boolean, _) => String::from("BOOLEAN"),
+    }
+}
+
+/// The integer types of the dialect by the number of digits they hold.
+fn integer_types(dialect: Dialect) -> &'static [(usize, &'static str)] {
+    match dialect {
+        Dialect::MySQL => &[
+            (3, "TINYINT"),
+            (5, "SMALLINT"),
+            (7, "MEDIUMINT"),
+            (10, "INT"),
+            (19, "BIGINT"),
+        ],
+        Dialect::Postgres => &[(5, "SMALLINT"), (10, "INTEGER"), (19, "BIGINT")],
+        Dialect::Sqlite => &[(19, "INTEGER")],
+    }
+}
+
+/// The number of digits of the smallest integer type of the dialect that
+/// holds the size, an integer without a size is a `BIGINT`.
+pub fn integer_width(size: Option<usize>, dialect: Dialect) -> usize {
+    let types = integer_types(dialect);
+    let (width, _) = types
+        .iter()
+        .find(|(width, _)| *width >= size.unwrap_or(19))
+        .unwrap_or(&types[types.len() - 1]);
+
+    *width
+}
+
+/// Render a default value as a SQL literal, numbers, booleans and SQL
+/// functions are kept as is, everything else is quoted as a string.
+pub fn default_literal(kind: &DataKind, value: &str) -> String {
+    let keyword = matches!(
+        value.to_ascii_uppercase().as_str(),
+        "NULL" | "CURRENT_TIMESTAMP" | "CURRENT_DATE" | "CURRENT_TIME" | "NOW()"
+    );
+
+    match kind {
+        _ if keyword => value.to_owned(),
+        DataKind::Integer(_) | DataKind::Float { .. } | DataKind::Year
+            if value.parse::<f64>().is_ok() =>
+        {
+            value.to_owned()
+        }
+        DataKind::Boolean => value.to_ascii_uppercase(),
+        _ => format!("'{}'", value.replace('\'', "''")),
+    }
+}
+
+fn quote_all(names: &[&str], dialect: Dialect) -> String {
+    names
+        .iter()
+        .map(|name| dialect.quote(name))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod test {
+    use omi::prelude::*;
+
+    use super::create_table;
+    use crate as omi;
+    use crate::driver::Dialect;
+
+    #[derive(Debug, Default, Clone, Entity)]
+    #[entity(table = "products")]
+    struct Product {
+        #[column(primary, auto)]
+        id: u64,
+
+        #[column(size = 255, default = "", index)]
+        title: String,
+
+        remark: Option<String>,
+    }
+
+    #[test]
+    fn test_create_table_mysql() {
+        let statements = create_table(&Product::meta().table, Dialect::MySQL).statements;
+
+        assert_eq!(
+            statements,
+            vec![
+                "CREATE TABLE `products` (\n  `id` BIGINT NOT NULL AUTO_INCREMENT,\n  `title` VARCHAR(255) NOT \
+                 NULL DEFAULT '',\n  `remark` TEXT,\n  PRIMARY KEY (`id`)\n)",
+                "CREATE INDEX `idx_products_title` ON `products` (`title`)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_create_table_sqlite() {
+        let statements = create_table(&Product::meta().table, Dialect::Sqlite).statements;
+
+        assert!(statements[0].contains("\"id\" INTEGER PRIMARY KEY AUTOINCREMENT"));
+        assert!(!statements[0].contains("PRIMARY KEY (\"id\")"));
+    }
+
+    #[test]
+    fn test_create_table_postgres() {
+        let statements = create_table(&Product::meta().table, Dialect::Postgres).statements;
+
+        assert!(statements[0].contains("\"id\" BIGSERIAL NOT NULL"));
+    }
+
+    #[derive(Debug, Default, Clone, Entity)]
+    #[entity(
+        table = "pages",
+        index(columns("tenant_id", "slug"), unique),
+        index(
+            columns(published_at),
+            where = "deleted_at IS NULL",
+            name = "idx_pages_live"
+        ),
+        check(expr = "length(slug) > 0")
+    )]
+    struct Page {
+        #[column(primary, auto)]
+        id: u64,
+
+        tenant_id: u64,
+
+        #[column(size = 64)]
+        slug: String,
+
+        #[column(type = "timestamp")]
+        published_at: Option<String>,
+
+        #[column(type = "timestamp")]
+        deleted_at: Option<String>,
+    }
+
+    #[test]
+    fn test_create_table_indexes() {
+        let statements = create_table(&Page::meta().table, Dialect::Postgres).statements;
+
+        assert!(statements[0].contains("CONSTRAINT \"chk_pages_1\" CHECK (length(slug) > 0)"));
+        assert_eq!(
+            statements[1..],
+            [
+                "CREATE UNIQUE INDEX \"idx_pages_tenant_id_slug\" ON \"pages\" (\"tenant_id\", \"slug\")",
+                "CREATE INDEX \"idx_pages_live\" ON \"pages\" (\"published_at\") WHERE deleted_at IS NULL",
+            ]
+        );
+
+        let changes = create_table(&Page::meta().table, Dialect::MySQL);
+        assert_eq!(changes.statements.len(), 2);
+        assert_eq!(
+            changes.warnings,
+            vec!["MySQL does not support partial indexes, index idx_pages_live of pages is not created"]
+        );
+    }
+
+    #[derive(Debug, Default, Clone, Entity)]
+    #[entity(table = "reviews")]
+    struct Review {
+        #[column(primary, auto)]
+        id: u64,
+
+        product_id: u64,
+
+        #[relation(belongs_to, on_delete = "cascade")]
+        product: Option<Product>,
+    }
+
+    #[test]
+    fn test_create_table_foreign_key() {
+        let statements = create_table(&Review::meta().table, Dialect::Postgres).statements;
+
+        assert!(statements[0].contains(
+            "CONSTRAINT \"fk_reviews_product_id\" FOREIGN KEY (\"product_id\") REFERENCES \"products\" \
+             (\"id\") ON DELETE CASCADE"
+        ));
+    }
+}
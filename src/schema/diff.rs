@@ -0,0 +1,688 @@
+// Copyright (c) The Amphitheatre Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::ddl::{
+    check_definition, column_definition, column_type, create_index, create_table,
+    create_table_index, default_literal, drop_table_index, foreign_key_definition, integer_width,
+    Changes,
+};
+use crate::driver::Dialect;
+use crate::model::{Check, Column, DataKind, ForeignKey, Index, Table};
+
+/// Generate the statements that migrate the `current` schema to the `target`
+/// schema. Only the tables of the target are compared, tables that exist in
+/// the database but not in the target are left untouched. Changes the
+/// dialect can't make are reported as warnings instead of statements.
+pub fn diff(current: &[Table], target: &[Table], dialect: Dialect) -> Changes {
+    let mut changes = Changes::default();
+
+    for table in sort_tables(target) {
+        match current.iter().find(|t| t.name == table.name) {
+            Some(existing) => changes.extend(diff_table(existing, table, dialect)),
+            None => changes.extend(create_table(table, dialect)),
+        }
+    }
+
+    changes
+}
+
+/// Order the tables so that referenced tables are created before the tables
+/// that reference them, tables in a reference cycle keep their given order.
+fn sort_tables(tables: &[Table]) -> Vec<&Table> {
+    let mut pending: Vec<&Table> = tables.iter().collect();
+    let mut sorted: Vec<&Table> = vec![];
+
+    while !pending.is_empty() {
+        let ready = pending.iter().position(|table| {
+            table.foreign_keys.iter().all(|fk| {
+                fk.referenced_table == table.name
+                    || sorted.iter().any(|t| t.name == fk.referenced_table)
+                    || !pending.iter().any(|t| t.name == fk.referenced_table)
+            })
+        });
+        sorted.push(pending.remove(ready.unwrap_or(0)));
+    }
+
+    sorted
+}
+
+fn diff_table(current: &Table, target: &Table, dialect: Dialect) -> Changes {
+    let table = dialect.quote(&target.name);
+    let mut changes = Changes::default();
+
+    for foreign_key in &current.foreign_keys {
+        if !target
+            .foreign_keys
+            .iter()
+            .any(|fk| same_foreign_key(fk, foreign_key))
+        {
+            changes.push(drop_foreign_key(current, foreign_key, dialect));
+        }
+    }
+
+    for check in &current.checks {
+        if !target.checks.iter().any(|c| c.name == check.name) {
+            changes.push(drop_check(current, check, dialect));
+        }
+    }
+
+    for column in &target.columns {
+        match current.columns.iter().find(|c| c.name == column.name) {
+            Some(existing) => {
+                if !same_column(existing, column, dialect) {
+                    changes.extend(alter_column(target, column, dialect));
+                }
+                changes.extend(diff_column_indexes(
+                    current, existing, target, column, dialect,
+                ));
+            }
+            None => {
+                changes.statements.push(format!(
+                    "ALTER TABLE {} ADD COLUMN {}",
+                    table,
+                    column_definition(column, dialect, false)
+                ));
+                if column.index && !column.unique && !column.primary {
+                    changes
+                        .statements
+                        .push(create_index(target, column, dialect));
+                }
+            }
+        }
+    }
+
+    for column in &current.columns {
+        if !target.columns.iter().any(|c| c.name == column.name) {
+            changes.statements.push(format!(
+                "ALTER TABLE {} DROP COLUMN {}",
+                table,
+                dialect.quote(&column.name)
+            ));
+        }
+    }
+
+    for foreign_key in &target.foreign_keys {
+        if !current
+            .foreign_keys
+            .iter()
+            .any(|fk| same_foreign_key(fk, foreign_key))
+        {
+            changes.push(add_foreign_key(target, foreign_key, dialect));
+        }
+    }
+
+    for check in &target.checks {
+        if !current.checks.iter().any(|c| c.name == check.name) {
+            changes.push(add_check(target, check, dialect));
+        }
+    }
+
+    // Indexes are matched by name, live indexes missing from the model are
+    // kept since they may back single column or unique constraints.
+    for index in &target.indexes {
+        match current.indexes.iter().find(|i| i.name == index.name) {
+            Some(existing) if same_index(existing, index) => {}
+            Some(existing) => {
+                changes
+                    .statements
+                    .push(drop_table_index(current, existing, dialect));
+                changes.push(create_table_index(target, index, dialect));
+            }
+            None => changes.push(create_table_index(target, index, dialect)),
+        }
+    }
+
+    changes
+}
+
+/// The statements that add or drop the single column index and unique index
+/// of an existing column. Live indexes are only dropped when they were
+/// created for the flags of the column, with the names `create_index()` and
+/// this function give them, or when they back the unique flag.
+fn diff_column_indexes(
+    current: &Table,
+    existing: &Column,
+    target: &Table,
+    column: &Column,
+    dialect: Dialect,
+) -> Changes {
+    let indexes: Vec<&Index> = current
+        .indexes
+        .iter()
+        .filter(|i| i.columns == [column.name.clone()] && i.condition.is_none())
+        .filter(|i| !target.indexes.iter().any(|t| t.name == i.name))
+        .collect();
+    let unique = indexes.iter().find(|i| i.unique);
+    let index = indexes
+        .iter()
+        .find(|i| !i.unique && i.name == format!("idx_{}_{}", current.name, column.name));
+
+    let mut changes = Changes::default();
+    let wants_index = column.index && !column.unique && !column.primary;
+    match (wants_index, existing.index || index.is_some()) {
+        (true, false) => changes
+            .statements
+            .push(create_index(target, column, dialect)),
+        (false, true) => {
+            if let Some(index) = index {
+                changes
+                    .statements
+                    .push(drop_table_index(current, index, dialect));
+            }
+        }
+        _ => {}
+    }
+
+    let wants_unique = column.unique && !column.primary;
+    match (wants_unique, existing.unique || unique.is_some()) {
+        (true, false) => changes.push(create_table_index(
+            target,
+            &Index {
+                name: format!("uq_{}_{}", target.name, column.name),
+                columns: vec![column.name.clone()],
+                unique: true,
+                condition: None,
+            },
+            dialect,
+        )),
+        (false, true) => {
+            if let Some(index) = unique {
+                changes
+                    .statements
+                    .push(drop_unique(current, index, dialect));
+            }
+        }
+        _ => {}
+    }
+
+    changes
+}
+
+/// Drop the index of a unique column, Postgres names the index of a unique
+/// constraint `{table}_{column}_key` and only drops it with the constraint.
+fn drop_unique(table: &Table, index: &Index, dialect: Dialect) -> String {
+    match dialect {
+        Dialect::Postgres if index.name.ends_with("_key") => format!(
+            "ALTER TABLE {} DROP CONSTRAINT {}",
+            dialect.quote(&table.name),
+            dialect.quote(&index.name)
+        ),
+        _ => drop_table_index(table, index, dialect),
+    }
+}
+
+fn add_check(table: &Table, check: &Check, dialect: Dialect) -> Result<String, String> {
+    match dialect {
+        Dialect::Sqlite => Err(format!(
+            "SQLite cannot add constraint {} to {}, rebuild the table to apply it",
+            check.name, table.name
+        )),
+        _ => Ok(format!(
+            "ALTER TABLE {} ADD {}",
+            dialect.quote(&table.name),
+            check_definition(check, dialect)
+        )),
+    }
+}
+
+fn drop_check(table: &Table, check: &Check, dialect: Dialect) -> Result<String, String> {
+    let name = dialect.quote(&table.name);
+    let constraint = dialect.quote(&check.name);
+
+    match dialect {
+        Dialect::MySQL => Ok(format!("ALTER TABLE {} DROP CHECK {}", name, constraint)),
+        Dialect::Postgres => Ok(format!(
+            "ALTER TABLE {} DROP CONSTRAINT {}",
+            name, constraint
+        )),
+        Dialect::Sqlite => Err(format!(
+            "SQLite cannot drop constraint {} of {}, rebuild the table to apply it",
+            check.name, table.name
+        )),
+    }
+}
+
+/// Whether the live index and the model index are equivalent, the predicate
+/// is compared loosely since databases rewrite it, and is skipped when the
+/// database does not report it.
+pub fn same_index(current: &Index, target: &Index) -> bool {
+    let normalize = |condition: &str| {
+        condition
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '(' && *c != ')')
+            .collect::<String>()
+            .to_ascii_lowercase()
+    };
+
+    current.columns == target.columns
+        && current.unique == target.unique
+        && match (&current.condition, &target.condition) {
+            (Some(current), Some(target)) => normalize(current) == normalize(target),
+            (Some(_), None) => false,
+            (None, _) => true,
+        }
+}
+
+fn add_foreign_key(
+    table: &Table,
+    foreign_key: &ForeignKey,
+    dialect: Dialect,
+) -> Result<String, String> {
+    match dialect {
+        Dialect::Sqlite => Err(format!(
+            "SQLite cannot add constraint {} to {}, rebuild the table to apply it",
+            foreign_key.name, table.name
+        )),
+        _ => Ok(format!(
+            "ALTER TABLE {} ADD {}",
+            dialect.quote(&table.name),
+            foreign_key_definition(foreign_key, dialect)
+        )),
+    }
+}
+
+fn drop_foreign_key(
+    table: &Table,
+    foreign_key: &ForeignKey,
+    dialect: Dialect,
+) -> Result<String, String> {
+    let name = dialect.quote(&table.name);
+    let constraint = dialect.quote(&foreign_key.name);
+
+    match dialect {
+        Dialect::MySQL => Ok(format!(
+            "ALTER TABLE {} DROP FOREIGN KEY {}",
+            name, constraint
+        )),
+        Dialect::Postgres => Ok(format!(
+            "ALTER TABLE {} DROP CONSTRAINT {}",
+            name, constraint
+        )),
+        Dialect::Sqlite => Err(format!(
+            "SQLite cannot drop constraint {} of {}, rebuild the table to apply it",
+            foreign_key.name, table.name
+        )),
+    }
+}
+
+/// Whether two foreign keys are equivalent, the constraint names are ignored
+/// since the database may have generated them.
+pub fn same_foreign_key(current: &ForeignKey, target: &ForeignKey) -> bool {
+    current.columns == target.columns
+        && current.referenced_table == target.referenced_table
+        && current.referenced_columns == target.referenced_columns
+        && current.on_delete == target.on_delete
+        && current.on_update == target.on_update
+}
+
+fn alter_column(table: &Table, column: &Column, dialect: Dialect) -> Changes {
+    let name = dialect.quote(&table.name);
+    let column_name = dialect.quote(&column.name);
+
+    let statements = match dialect {
+        Dialect::MySQL => vec![format!(
+            "ALTER TABLE {} MODIFY COLUMN {}",
+            name,
+            column_definition(column, dialect, false)
+        )],
+        Dialect::Postgres => vec![
+            format!(
+                "ALTER TABLE {} ALTER COLUMN {} TYPE {}",
+                name,
+                column_name,
+                column_type(column, dialect)
+            ),
+            match column.null {
+                true => format!(
+                    "ALTER TABLE {} ALTER COLUMN {} DROP NOT NULL",
+                    name, column_name
+                ),
+                false => format!(
+                    "ALTER TABLE {} ALTER COLUMN {} SET NOT NULL",
+                    name, column_name
+                ),
+            },
+            match &column.default {
+                Some(default) => format!(
+                    "ALTER TABLE {} ALTER COLUMN {} SET DEFAULT {}",
+                    name,
+                    column_name,
+                    default_literal(&column.kind, default)
+                ),
+                None => format!(
+                    "ALTER TABLE {} ALTER COLUMN {} DROP DEFAULT",
+                    name, column_name
+                ),
+            },
+        ],
+        // SQLite can't alter a column in place, the table has to be rebuilt by hand.
+        Dialect::Sqlite => {
+            return Changes {
+                statements: vec![],
+                warnings: vec![format!(
+                    "SQLite cannot alter column {}.{}, rebuild the table to apply it",
+                    table.name, column.name
+                )],
+            }
+        }
+    };
+
+    Changes {
+        statements,
+        warnings: vec![],
+    }
+}
+
+/// Whether the live column and the model column are equivalent for the
+/// dialect, the kinds are compared once normalized for the dialect.
+pub fn same_column(current: &Column, target: &Column, dialect: Dialect) -> bool {
+    normalize_kind(&current.kind, dialect) == normalize_kind(&target.kind, dialect)
+        && (dialect != Dialect::Postgres || current.auto == target.auto)
+        && current.null == target.null
+        && same_default(
+            &target.kind,
+            current.default.as_deref(),
+            target.default.as_deref(),
+        )
+}
+
+/// The kind as the dialect stores it, integers by the digits of their type,
+/// and timestamps and datetimes alike on Postgres.
+fn normalize_kind(kind: &DataKind, dialect: Dialect) -> DataKind {
+    match (kind, dialect) {
+        (DataKind::Integer(size), _) => DataKind::Integer(Some(integer_width(*size, dialect))),
+        (DataKind::Datetime, Dialect::Postgres) => DataKind::Timestamp,
+        (kind, _) => kind.clone(),
+    }
+}
+
+fn same_default(kind: &DataKind, current: Option<&str>, target: Option<&str>) -> bool {
+    let normalize = |value: &str| match (kind, value.to_ascii_lowercase().as_str()) {
+        (DataKind::Boolean, "1" | "true") => String::from("true"),
+        (DataKind::Boolean, "0" | "false") => String::from("false"),
+        (_, value) => value.to_owned(),
+    };
+
+    current.map(normalize) == target.map(normalize)
+}
+
+#[cfg(test)]
+mod test {
+    use omi::prelude::*;
+
+    use super::diff;
+    use crate as omi;
+    use crate::driver::Dialect;
+    use crate::model::{Check, DataKind, Index, ReferentialAction, Table};
+
+    #[derive(Debug, Default, Clone, Entity)]
+    #[entity(table = "products")]
+    struct Product {
+        #[column(primary, auto)]
+        id: u64,
+
+        #[column(size = 255, default = "")]
+        title: String,
+
+        #[column(default = "true")]
+        enabled: bool,
+    }
+
+    #[derive(Debug, Default, Clone, Entity)]
+    #[entity(table = "reviews")]
+    struct Review {
+        #[column(primary, auto)]
+        id: u64,
+
+        product_id: u64,
+
+        #[relation(belongs_to, on_delete = "cascade")]
+        product: Option<Product>,
+    }
+
+    #[test]
+    fn test_diff_create_missing_table() {
+        let statements = diff(&[], &[Product::meta().table], Dialect::MySQL).statements;
+        assert!(statements[0].starts_with("CREATE TABLE `products`"));
+    }
+
+    #[test]
+    fn test_diff_unchanged_table() {
+        let mut current = Product::meta().table;
+        current.columns[2].default = Some("1".into());
+
+        assert!(diff(&[current], &[Product::meta().table], Dialect::MySQL).is_empty());
+    }
+
+    #[test]
+    fn test_diff_columns() {
+        let mut current: Table = Product::meta().table;
+        current.columns.remove(2);
+        current.columns[1].kind = DataKind::Text(None);
+        current.columns.push(omi::model::Column {
+            name: "legacy".into(),
+            kind: DataKind::Boolean,
+            null: true,
+            primary: false,
+            default: None,
+            auto: false,
+            unique: false,
+            index: false,
+        });
+
+        assert_eq!(
+            diff(&[current], &[Product::meta().table], Dialect::MySQL).statements,
+            vec![
+                "ALTER TABLE `products` MODIFY COLUMN `title` VARCHAR(255) NOT NULL DEFAULT ''",
+                "ALTER TABLE `products` ADD COLUMN `enabled` BOOLEAN NOT NULL DEFAULT TRUE",
+                "ALTER TABLE `products` DROP COLUMN `legacy`",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_create_referenced_table_first() {
+        let statements = diff(
+            &[],
+            &[Review::meta().table, Product::meta().table],
+            Dialect::MySQL,
+        )
+        .statements;
+
+        assert!(statements[0].starts_with("CREATE TABLE `products`"));
+        assert!(statements[1].starts_with("CREATE TABLE `reviews`"));
+    }
+
+    #[test]
+    fn test_diff_foreign_keys() {
+        let mut current = Review::meta().table;
+        current.foreign_keys[0].name = "reviews_ibfk_1".into();
+        current.foreign_keys[0].on_delete = ReferentialAction::Restrict;
+
+        assert_eq!(
+            diff(&[current], &[Review::meta().table], Dialect::MySQL).statements,
+            vec![
+                "ALTER TABLE `reviews` DROP FOREIGN KEY `reviews_ibfk_1`",
+                "ALTER TABLE `reviews` ADD CONSTRAINT `fk_reviews_product_id` FOREIGN KEY (`product_id`) \
+                 REFERENCES `products` (`id`) ON DELETE CASCADE",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_sqlite_warnings() {
+        let mut current = Review::meta().table;
+        current.foreign_keys.clear();
+        current.columns[1].null = true;
+
+        let changes = diff(&[current], &[Review::meta().table], Dialect::Sqlite);
+        assert!(changes.statements.is_empty());
+        assert_eq!(
+            changes.warnings,
+            vec![
+                "SQLite cannot alter column reviews.product_id, rebuild the table to apply it",
+                "SQLite cannot add constraint fk_reviews_product_id to reviews, rebuild the table to apply it",
+            ]
+        );
+    }
+
+    #[derive(Debug, Default, Clone, Entity)]
+    #[entity(
+        table = "pages",
+        index(columns("tenant_id", "slug"), unique),
+        index(columns("slug"), where = "deleted_at IS NULL", name = "idx_pages_live"),
+        check(expr = "length(slug) > 0", name = "chk_pages_slug")
+    )]
+    struct Page {
+        #[column(primary, auto)]
+        id: u64,
+
+        tenant_id: u64,
+
+        slug: String,
+
+        #[column(type = "timestamp")]
+        deleted_at: Option<String>,
+    }
+
+    #[test]
+    fn test_diff_indexes_and_checks() {
+        let mut current = Page::meta().table;
+        current.indexes[0].unique = false;
+        current.indexes[1].condition = Some("(deleted_at IS NULL)".into());
+        current.checks = vec![Check {
+            name: "chk_pages_legacy".into(),
+            expression: "tenant_id > 0".into(),
+        }];
+        current.indexes.push(Index {
+            name: "pages_slug_idx".into(),
+            columns: vec!["slug".into()],
+            unique: false,
+            condition: None,
+        });
+
+        assert_eq!(
+            diff(&[current], &[Page::meta().table], Dialect::Postgres).statements,
+            vec![
+                "ALTER TABLE \"pages\" DROP CONSTRAINT \"chk_pages_legacy\"",
+                "ALTER TABLE \"pages\" ADD CONSTRAINT \"chk_pages_slug\" CHECK (length(slug) > 0)",
+                "DROP INDEX \"idx_pages_tenant_id_slug\"",
+                "CREATE UNIQUE INDEX \"idx_pages_tenant_id_slug\" ON \"pages\" (\"tenant_id\", \"slug\")",
+            ]
+        );
+    }
+
+    #[derive(Debug, Default, Clone, Entity)]
+    #[entity(table = "users")]
+    struct User {
+        #[column(primary, auto)]
+        id: u64,
+
+        #[column(size = 10)]
+        age: u32,
+
+        #[column(index)]
+        name: String,
+
+        #[column(unique)]
+        email: String,
+
+        nickname: String,
+    }
+
+    fn int(size: usize) -> DataKind {
+        DataKind::Integer(Some(size))
+    }
+
+    #[test]
+    fn test_diff_integer_types() {
+        let mut current = User::meta().table;
+        current.columns[0].kind = int(19);
+        current.columns[1].kind = int(10);
+        assert!(diff(&[current.clone()], &[User::meta().table], Dialect::MySQL).is_empty());
+
+        current.columns[0].kind = int(10);
+        current.columns[1].kind = int(19);
+        assert_eq!(
+            diff(&[current.clone()], &[User::meta().table], Dialect::MySQL).statements,
+            vec![
+                "ALTER TABLE `users` MODIFY COLUMN `id` BIGINT NOT NULL AUTO_INCREMENT",
+                "ALTER TABLE `users` MODIFY COLUMN `age` INT NOT NULL",
+            ]
+        );
+
+        current.columns[0].kind = int(19);
+        assert_eq!(
+            diff(&[current], &[User::meta().table], Dialect::Postgres).statements,
+            vec![
+                "ALTER TABLE \"users\" ALTER COLUMN \"age\" TYPE INTEGER",
+                "ALTER TABLE \"users\" ALTER COLUMN \"age\" SET NOT NULL",
+                "ALTER TABLE \"users\" ALTER COLUMN \"age\" DROP DEFAULT",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_column_indexes() {
+        let mut current = User::meta().table;
+        for column in current.columns.iter_mut() {
+            column.index = false;
+            column.unique = false;
+        }
+        current.indexes = vec![Index {
+            name: "users_nickname_key".into(),
+            columns: vec!["nickname".into()],
+            unique: true,
+            condition: None,
+        }];
+
+        assert_eq!(
+            diff(&[current], &[User::meta().table], Dialect::Postgres).statements,
+            vec![
+                "CREATE INDEX \"idx_users_name\" ON \"users\" (\"name\")",
+                "CREATE UNIQUE INDEX \"uq_users_email\" ON \"users\" (\"email\")",
+                "ALTER TABLE \"users\" DROP CONSTRAINT \"users_nickname_key\"",
+            ]
+        );
+
+        let mut current = User::meta().table;
+        current.columns[2].index = false;
+        current.columns[3].unique = false;
+        current.indexes = vec![
+            Index {
+                name: "idx_users_name".into(),
+                columns: vec!["name".into()],
+                unique: false,
+                condition: None,
+            },
+            Index {
+                name: "email".into(),
+                columns: vec!["email".into()],
+                unique: true,
+                condition: None,
+            },
+        ];
+        let mut target = User::meta().table;
+        target.columns[2].index = false;
+        target.columns[3].unique = false;
+
+        assert_eq!(
+            diff(&[current], &[target], Dialect::MySQL).statements,
+            vec![
+                "DROP INDEX `idx_users_name` ON `users`",
+                "DROP INDEX `email` ON `users`",
+            ]
+        );
+    }
+}
@@ -0,0 +1,466 @@
+// Copyright (c) The Amphitheatre Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::driver::Dialect;
+use crate::model::{Check, Column, DataKind, ForeignKey, Index, ReferentialAction, Table};
+use crate::{Database, Result};
+
+/// A column row read from the catalog of the database, the fields are named
+/// after the aliases selected by the introspection queries.
+#[derive(Debug, Default, Clone)]
+pub struct CatalogColumn {
+    pub table_name: String,
+    pub column_name: String,
+    pub column_type: String,
+    pub character_length: Option<usize>,
+    pub numeric_precision: Option<usize>,
+    pub numeric_scale: Option<usize>,
+    pub nullable: bool,
+    pub column_default: Option<String>,
+    pub primary: bool,
+    pub auto: bool,
+}
+
+/// A foreign key row read from the catalog of the database, one row per
+/// referencing column.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CatalogForeignKey {
+    pub constraint_name: String,
+    pub table_name: String,
+    pub column_name: String,
+    pub referenced_table: String,
+    pub referenced_column: String,
+    pub on_delete: String,
+    pub on_update: String,
+}
+
+/// An index row read from the catalog of the database, one row per indexed
+/// column. Primary keys are not included.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CatalogIndex {
+    pub index_name: String,
+    pub table_name: String,
+    pub column_name: String,
+    pub unique: bool,
+    pub condition: Option<String>,
+}
+
+/// A check constraint row read from the catalog of the database.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CatalogCheck {
+    pub constraint_name: String,
+    pub table_name: String,
+    pub expression: String,
+}
+
+/// Read all tables of the current database (or schema) into the model.
+pub fn tables(db: &Database) -> Result<Vec<Table>> {
+    let dialect = db.dialect()?;
+    let rows = db.execute::<CatalogColumn>(columns_sql(dialect).into())?;
+
+    let mut tables: Vec<Table> = vec![];
+    for row in rows {
+        let column = parse_column(&row, dialect);
+        match tables.last_mut() {
+            Some(table) if table.name == row.table_name => table.columns.push(column),
+            _ => tables.push(Table {
+                name: row.table_name,
+                columns: vec![column],
+                ..Default::default()
+            }),
+        }
+    }
+
+    let rows = db.execute::<CatalogForeignKey>(foreign_keys_sql(dialect).into())?;
+    for row in rows {
+        if let Some(table) = tables.iter_mut().find(|t| t.name == row.table_name) {
+            add_foreign_key(table, row);
+        }
+    }
+
+    let rows = db.execute::<CatalogIndex>(indexes_sql(dialect).into())?;
+    for row in rows {
+        if let Some(table) = tables.iter_mut().find(|t| t.name == row.table_name) {
+            add_index(table, row);
+        }
+    }
+
+    // SQLite keeps check constraints only in the table source.
+    if let Some(sql) = checks_sql(dialect) {
+        let rows = db.execute::<CatalogCheck>(sql.into())?;
+        for row in rows {
+            if let Some(table) = tables.iter_mut().find(|t| t.name == row.table_name) {
+                table.checks.push(Check {
+                    name: row.constraint_name,
+                    expression: row.expression,
+                });
+            }
+        }
+    }
+
+    Ok(tables)
+}
+
+/// Fold a catalog row into the indexes of the table, the columns of a
+/// composite index are read as consecutive rows of the same index.
+pub fn add_index(table: &mut Table, row: CatalogIndex) {
+    match table.indexes.last_mut() {
+        Some(index) if index.name == row.index_name => index.columns.push(row.column_name),
+        _ => table.indexes.push(Index {
+            name: row.index_name,
+            columns: vec![row.column_name],
+            unique: row.unique,
+            condition: row.condition,
+        }),
+    }
+}
+
+/// Fold a catalog row into the foreign keys of the table, the columns of a
+/// composite key are read as consecutive rows of the same constraint.
+pub fn add_foreign_key(table: &mut Table, row: CatalogForeignKey) {
+    match table.foreign_keys.last_mut() {
+        Some(fk) if fk.name == row.constraint_name => {
+            fk.columns.push(row.column_name);
+            fk.referenced_columns.push(row.referenced_column);
+        }
+        _ => table.foreign_keys.push(ForeignKey {
+            name: row.constraint_name,
+            columns: vec![row.column_name],
+            referenced_table: row.referenced_table,
+            referenced_columns: vec![row.referenced_column],
+            on_delete: ReferentialAction::parse(&row.on_delete).unwrap_or_default(),
+            on_update: ReferentialAction::parse(&row.on_update).unwrap_or_default(),
+        }),
+    }
+}
+
+/// Read a single table of the current database into the model, `None` will be
+/// returned if the table does not exist.
+pub fn table(db: &Database, name: &str) -> Result<Option<Table>> {
+    Ok(tables(db)?.into_iter().find(|table| table.name == name))
+}
+
+fn foreign_keys_sql(dialect: Dialect) -> &'static str {
+    match dialect {
+        Dialect::MySQL => {
+            "SELECT k.CONSTRAINT_NAME AS constraint_name, k.TABLE_NAME AS table_name, k.COLUMN_NAME \
+             AS column_name, k.REFERENCED_TABLE_NAME AS referenced_table, k.REFERENCED_COLUMN_NAME AS \
+             referenced_column, r.DELETE_RULE AS on_delete, r.UPDATE_RULE AS on_update FROM \
+             information_schema.KEY_COLUMN_USAGE k JOIN information_schema.REFERENTIAL_CONSTRAINTS r \
+             ON r.CONSTRAINT_SCHEMA = k.TABLE_SCHEMA AND r.CONSTRAINT_NAME = k.CONSTRAINT_NAME WHERE \
+             k.TABLE_SCHEMA = DATABASE() AND k.REFERENCED_TABLE_NAME IS NOT NULL ORDER BY \
+             k.TABLE_NAME, k.CONSTRAINT_NAME, k.ORDINAL_POSITION"
+        }
+        Dialect::Postgres => {
+            "SELECT kcu.constraint_name, kcu.table_name, kcu.column_name, ccu.table_name AS \
+             referenced_table, ccu.column_name AS referenced_column, rc.delete_rule AS on_delete, \
+             rc.update_rule AS on_update FROM information_schema.referential_constraints rc JOIN \
+             information_schema.key_column_usage kcu ON kcu.constraint_name = rc.constraint_name AND \
+             kcu.constraint_schema = rc.constraint_schema JOIN information_schema.key_column_usage ccu \
+             ON ccu.constraint_name = rc.unique_constraint_name AND ccu.constraint_schema = \
+             rc.unique_constraint_schema AND ccu.ordinal_position = kcu.position_in_unique_constraint \
+             WHERE rc.constraint_schema = current_schema() ORDER BY kcu.table_name, \
+             kcu.constraint_name, kcu.ordinal_position"
+        }
+        Dialect::Sqlite => {
+            "SELECT 'fk_' || m.name || '_' || f.id AS constraint_name, m.name AS table_name, \
+             f.\"from\" AS column_name, f.\"table\" AS referenced_table, f.\"to\" AS \
+             referenced_column, f.on_delete, f.on_update FROM sqlite_master m JOIN \
+             pragma_foreign_key_list(m.name) f WHERE m.type = 'table' ORDER BY m.name, f.id, f.seq"
+        }
+    }
+}
+
+fn indexes_sql(dialect: Dialect) -> &'static str {
+    match dialect {
+        Dialect::MySQL => {
+            "SELECT INDEX_NAME AS index_name, TABLE_NAME AS table_name, COLUMN_NAME AS column_name, \
+             NON_UNIQUE = 0 AS `unique`, NULL AS `condition` FROM information_schema.STATISTICS WHERE \
+             TABLE_SCHEMA = DATABASE() AND INDEX_NAME <> 'PRIMARY' ORDER BY TABLE_NAME, INDEX_NAME, \
+             SEQ_IN_INDEX"
+        }
+        Dialect::Postgres => {
+            "SELECT i.relname AS index_name, t.relname AS table_name, a.attname AS column_name, \
+             ix.indisunique AS \"unique\", pg_get_expr(ix.indpred, ix.indrelid) AS \"condition\" FROM \
+             pg_index ix JOIN pg_class t ON t.oid = ix.indrelid JOIN pg_class i ON i.oid = \
+             ix.indexrelid JOIN pg_namespace n ON n.oid = t.relnamespace JOIN LATERAL \
+             unnest(ix.indkey) WITH ORDINALITY k(attnum, ord) ON true JOIN pg_attribute a ON \
+             a.attrelid = t.oid AND a.attnum = k.attnum WHERE n.nspname = current_schema() AND NOT \
+             ix.indisprimary ORDER BY t.relname, i.relname, k.ord"
+        }
+        Dialect::Sqlite => {
+            "SELECT l.name AS index_name, m.name AS table_name, i.name AS column_name, l.\"unique\" AS \
+             \"unique\", NULL AS \"condition\" FROM sqlite_master m JOIN pragma_index_list(m.name) l \
+             JOIN pragma_index_info(l.name) i WHERE m.type = 'table' AND l.origin <> 'pk' ORDER BY \
+             m.name, l.name, i.seqno"
+        }
+    }
+}
+
+fn checks_sql(dialect: Dialect) -> Option<&'static str> {
+    match dialect {
+        Dialect::MySQL => Some(
+            "SELECT c.CONSTRAINT_NAME AS constraint_name, t.TABLE_NAME AS table_name, \
+             c.CHECK_CLAUSE AS expression FROM information_schema.CHECK_CONSTRAINTS c JOIN \
+             information_schema.TABLE_CONSTRAINTS t ON t.CONSTRAINT_SCHEMA = c.CONSTRAINT_SCHEMA AND \
+             t.CONSTRAINT_NAME = c.CONSTRAINT_NAME WHERE c.CONSTRAINT_SCHEMA = DATABASE() ORDER BY \
+             t.TABLE_NAME, c.CONSTRAINT_NAME",
+        ),
+        Dialect::Postgres => Some(
+            "SELECT c.conname AS constraint_name, t.relname AS table_name, \
+             pg_get_expr(c.conbin, c.conrelid) AS expression FROM pg_constraint c JOIN pg_class t ON \
+             t.oid = c.conrelid JOIN pg_namespace n ON n.oid = t.relnamespace WHERE c.contype = 'c' \
+             AND n.nspname = current_schema() ORDER BY t.relname, c.conname",
+        ),
+        Dialect::Sqlite => None,
+    }
+}
+
+fn columns_sql(dialect: Dialect) -> &'static str {
+    match dialect {
+        Dialect::MySQL => {
+            "SELECT TABLE_NAME AS table_name, COLUMN_NAME AS column_name, COLUMN_TYPE AS column_type, \
+             CHARACTER_MAXIMUM_LENGTH AS character_length, NUMERIC_PRECISION AS numeric_precision, \
+             NUMERIC_SCALE AS numeric_scale, IS_NULLABLE = 'YES' AS nullable, COLUMN_DEFAULT AS \
+             column_default, COLUMN_KEY = 'PRI' AS `primary`, EXTRA LIKE '%auto_increment%' AS auto \
+             FROM information_schema.COLUMNS WHERE TABLE_SCHEMA = DATABASE() \
+             ORDER BY TABLE_NAME, ORDINAL_POSITION"
+        }
+        Dialect::Postgres => {
+            "SELECT c.table_name, c.column_name, c.data_type AS column_type, c.character_maximum_length \
+             AS character_length, c.numeric_precision, c.numeric_scale, c.is_nullable = 'YES' AS \
+             nullable, c.column_default, EXISTS (SELECT 1 FROM information_schema.table_constraints tc \
+             JOIN information_schema.key_column_usage k ON k.constraint_name = tc.constraint_name AND \
+             k.table_schema = tc.table_schema WHERE tc.constraint_type = 'PRIMARY KEY' AND \
+             tc.table_schema = c.table_schema AND tc.table_name = c.table_name AND k.column_name = \
+             c.column_name) AS \"primary\", COALESCE(c.column_default LIKE 'nextval(%', false) OR \
+             c.is_identity = 'YES' AS auto FROM information_schema.columns c WHERE c.table_schema = \
+             current_schema() ORDER BY c.table_name, c.ordinal_position"
+        }
+        Dialect::Sqlite => {
+            "SELECT m.name AS table_name, p.name AS column_name, p.type AS column_type, NULL AS \
+             character_length, NULL AS numeric_precision, NULL AS numeric_scale, p.\"notnull\" = 0 AS \
+             nullable, p.dflt_value AS column_default, p.pk > 0 AS \"primary\", p.pk > 0 AND \
+             lower(p.type) = 'integer' AS auto FROM sqlite_master m JOIN pragma_table_info(m.name) p \
+             WHERE m.type = 'table' AND m.name NOT LIKE 'sqlite_%' ORDER BY m.name, p.cid"
+        }
+    }
+}
+
+fn parse_column(row: &CatalogColumn, dialect: Dialect) -> Column {
+    Column {
+        name: row.column_name.clone(),
+        // SQLite integers are all 64 bit, whatever the declared type.
+        kind: match (parse_kind(row), dialect) {
+            (DataKind::Integer(_), Dialect::Sqlite) => DataKind::Integer(None),
+            (kind, _) => kind,
+        },
+        null: row.nullable,
+        primary: row.primary,
+        default: match row.auto {
+            true => None,
+            false => parse_default(row.column_default.as_deref(), dialect),
+        },
+        auto: row.auto,
+        unique: false,
+        index: false,
+    }
+}
+
+/// Parse a catalog type name, such as `varchar(255)`, `bigint(20) unsigned`
+/// or `timestamp without time zone` into a data kind.
+pub fn parse_kind(row: &CatalogColumn) -> DataKind {
+    let column_type = row.column_type.trim().to_ascii_lowercase();
+    let (base, args) = match column_type.split_once('(') {
+        Some((base, rest)) => {
+            let args: Vec<usize> = rest
+                .split(')')
+                .next()
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|arg| arg.trim().parse().ok())
+                .collect();
+            (base.trim().to_owned(), args)
+        }
+        None => (column_type.trim_end_matches(" unsigned").to_owned(), vec![]),
+    };
+
+    match base.as_str() {
+        "tinyint" if args.first() == Some(&1) => DataKind::Boolean,
+        "bool" | "boolean" => DataKind::Boolean,
+        // The size of an integer is the number of digits of its type, the
+        // display width of MySQL doesn't limit the values.
+        "tinyint" => DataKind::Integer(Some(3)),
+        "smallint" | "smallserial" => DataKind::Integer(Some(5)),
+        "mediumint" => DataKind::Integer(Some(7)),
+        "int" | "integer" | "serial" => DataKind::Integer(Some(10)),
+        "bigint" | "bigserial" => DataKind::Integer(Some(19)),
+        "decimal" | "numeric" | "dec" | "fixed" => DataKind::Float {
+            max_digits: args.first().copied().or(row.numeric_precision),
+            decimal_places: args.get(1).copied().or(row.numeric_scale),
+        },
+        "float" | "double" | "real" | "double precision" => DataKind::Float {
+            max_digits: None,
+            decimal_places: None,
+        },
+        "char" | "varchar" | "character" | "character varying" | "nchar" | "nvarchar" => {
+            DataKind::Text(args.first().copied().or(row.character_length))
+        }
+        "blob" | "tinyblob" | "mediumblob" | "longblob" | "bytea" | "binary" | "varbinary" => {
+            DataKind::Blob
+        }
+        "datetime" => DataKind::Datetime,
+        "date" => DataKind::Date,
+        "year" => DataKind::Year,
+        base if base.starts_with("timestamp") => DataKind::Timestamp,
+        base if base.starts_with("time") => DataKind::Time,
+        _ => DataKind::Text(None),
+    }
+}
+
+/// Normalize a catalog default expression into the plain value used by the
+/// model, e.g. `'abc'::character varying` becomes `abc`.
+pub fn parse_default(value: Option<&str>, dialect: Dialect) -> Option<String> {
+    let mut value = value?.trim();
+
+    if dialect == Dialect::Postgres {
+        if value.starts_with("nextval(") {
+            return None;
+        }
+        if let Some((literal, _)) = value.rsplit_once("::") {
+            value = literal;
+        }
+    }
+
+    if value.eq_ignore_ascii_case("null") {
+        return None;
+    }
+
+    match value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+        true => Some(value[1..value.len() - 1].replace("''", "'")),
+        false => Some(value.to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        add_foreign_key, add_index, parse_default, parse_kind, CatalogColumn, CatalogForeignKey,
+        CatalogIndex,
+    };
+    use crate::driver::Dialect;
+    use crate::model::{DataKind, ReferentialAction, Table};
+
+    fn kind_of(column_type: &str) -> DataKind {
+        parse_kind(&CatalogColumn {
+            column_type: column_type.into(),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_parse_kind() {
+        assert_eq!(kind_of("bigint(20) unsigned"), DataKind::Integer(Some(19)));
+        assert_eq!(kind_of("int(11)"), DataKind::Integer(Some(10)));
+        assert_eq!(kind_of("integer"), DataKind::Integer(Some(10)));
+        assert_eq!(kind_of("tinyint(1)"), DataKind::Boolean);
+        assert_eq!(kind_of("varchar(255)"), DataKind::Text(Some(255)));
+        assert_eq!(
+            kind_of("decimal(8,2)"),
+            DataKind::Float {
+                max_digits: Some(8),
+                decimal_places: Some(2)
+            }
+        );
+        assert_eq!(kind_of("timestamp without time zone"), DataKind::Timestamp);
+        assert_eq!(kind_of("time"), DataKind::Time);
+        assert_eq!(kind_of("jsonb"), DataKind::Text(None));
+    }
+
+    #[test]
+    fn test_parse_default() {
+        assert_eq!(
+            parse_default(Some("'abc'::character varying"), Dialect::Postgres),
+            Some("abc".into())
+        );
+        assert_eq!(
+            parse_default(
+                Some("nextval('products_id_seq'::regclass)"),
+                Dialect::Postgres
+            ),
+            None
+        );
+        assert_eq!(
+            parse_default(Some("'it''s'"), Dialect::Sqlite),
+            Some("it's".into())
+        );
+        assert_eq!(parse_default(Some(""), Dialect::MySQL), Some("".into()));
+        assert_eq!(parse_default(None, Dialect::MySQL), None);
+    }
+
+    #[test]
+    fn test_add_foreign_key() {
+        let mut table = Table {
+            name: "stocks".into(),
+            ..Default::default()
+        };
+        for (column, referenced) in [("warehouse_id", "id"), ("product_code", "code")] {
+            add_foreign_key(
+                &mut table,
+                CatalogForeignKey {
+                    constraint_name: "fk_stocks_product".into(),
+                    table_name: "stocks".into(),
+                    column_name: column.into(),
+                    referenced_table: "products".into(),
+                    referenced_column: referenced.into(),
+                    on_delete: "SET NULL".into(),
+                    on_update: "NO ACTION".into(),
+                },
+            );
+        }
+
+        assert_eq!(table.foreign_keys.len(), 1);
+        assert_eq!(
+            table.foreign_keys[0].columns,
+            vec!["warehouse_id", "product_code"]
+        );
+        assert_eq!(table.foreign_keys[0].referenced_columns, vec!["id", "code"]);
+        assert_eq!(table.foreign_keys[0].on_delete, ReferentialAction::SetNull);
+        assert_eq!(table.foreign_keys[0].on_update, ReferentialAction::NoAction);
+    }
+
+    #[test]
+    fn test_add_index() {
+        let mut table = Table {
+            name: "pages".into(),
+            ..Default::default()
+        };
+        for column in ["tenant_id", "slug"] {
+            add_index(
+                &mut table,
+                CatalogIndex {
+                    index_name: "idx_pages_tenant_id_slug".into(),
+                    table_name: "pages".into(),
+                    column_name: column.into(),
+                    unique: true,
+                    condition: None,
+                },
+            );
+        }
+
+        assert_eq!(table.indexes.len(), 1);
+        assert_eq!(table.indexes[0].columns, vec!["tenant_id", "slug"]);
+        assert!(table.indexes[0].unique);
+    }
+}
@@ -0,0 +1,27 @@
+// Copyright (c) The Amphitheatre Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Provides schema introspection, validation, DDL generation and schema diff
+
+mod ddl;
+pub use self::ddl::*;
+
+mod diff;
+pub use self::diff::*;
+
+mod introspect;
+pub use self::introspect::*;
+
+mod validate;
+pub use self::validate::*;
@@ -0,0 +1,287 @@
+// Copyright (c) The Amphitheatre Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+
+use super::ddl::kind_type;
+use crate::driver::Dialect;
+use crate::model::{DataKind, Table};
+
+/// A difference between the model of an entity and the live table which
+/// would break reading or writing the entity.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mismatch {
+    /// The table does not exist.
+    MissingTable { table: String },
+    /// The column of a field does not exist.
+    MissingColumn { table: String, column: String },
+    /// The column type can't hold the values of the field.
+    IncompatibleType {
+        table: String,
+        column: String,
+        expected: String,
+        found: String,
+    },
+    /// The field is an `Option` but the column is `NOT NULL`, or the other
+    /// way around.
+    Nullability {
+        table: String,
+        column: String,
+        field_null: bool,
+    },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Mismatch::MissingTable { table } => write!(f, "{}: table does not exist", table),
+            Mismatch::MissingColumn { table, column } => {
+                write!(f, "{}.{}: column does not exist", table, column)
+            }
+            Mismatch::IncompatibleType {
+                table,
+                column,
+                expected,
+                found,
+            } => write!(
+                f,
+                "{}.{}: incompatible type, expected {} but found {}",
+                table, column, expected, found
+            ),
+            Mismatch::Nullability {
+                table,
+                column,
+                field_null: true,
+            } => write!(
+                f,
+                "{}.{}: the field is an Option but the column is NOT NULL",
+                table, column
+            ),
+            Mismatch::Nullability {
+                table,
+                column,
+                field_null: false,
+            } => write!(
+                f,
+                "{}.{}: the column is nullable but the field is not an Option",
+                table, column
+            ),
+        }
+    }
+}
+
+/// The result of validating entities against the live database.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ValidationReport {
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl ValidationReport {
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "schema validation found {} mismatch(es):",
+            self.mismatches.len()
+        )?;
+        for mismatch in &self.mismatches {
+            write!(f, "\n  - {}", mismatch)?;
+        }
+        Ok(())
+    }
+}
+
+/// Validate the model of an entity against the live tables.
+pub fn validate(model: &Table, live: &[Table], dialect: Dialect) -> Vec<Mismatch> {
+    let table = match live.iter().find(|t| t.name == model.name) {
+        Some(table) => table,
+        None => {
+            return vec![Mismatch::MissingTable {
+                table: model.name.clone(),
+            }]
+        }
+    };
+
+    let mut mismatches = vec![];
+    for column in &model.columns {
+        let existing = match table.columns.iter().find(|c| c.name == column.name) {
+            Some(existing) => existing,
+            None => {
+                mismatches.push(Mismatch::MissingColumn {
+                    table: model.name.clone(),
+                    column: column.name.clone(),
+                });
+                continue;
+            }
+        };
+
+        if !compatible(&column.kind, &existing.kind) {
+            mismatches.push(Mismatch::IncompatibleType {
+                table: model.name.clone(),
+                column: column.name.clone(),
+                expected: kind_type(&column.kind, dialect),
+                found: kind_type(&existing.kind, dialect),
+            });
+        }
+
+        // Primary keys are never NULL, whatever the catalog reports.
+        if column.null != existing.null && !existing.primary {
+            mismatches.push(Mismatch::Nullability {
+                table: model.name.clone(),
+                column: column.name.clone(),
+                field_null: column.null,
+            });
+        }
+    }
+
+    mismatches
+}
+
+/// Whether a column of the `live` kind can hold the values of the `model`
+/// kind, sizes and precisions only matter when the live column is smaller.
+/// Integers and floats don't round trip through each other, and a boolean
+/// only fits an integer column of size 1, such as MySQL's `TINYINT(1)`.
+fn compatible(model: &DataKind, live: &DataKind) -> bool {
+    match (model, live) {
+        (DataKind::Integer(Some(size)), DataKind::Integer(Some(limit))) => size <= limit,
+        (DataKind::Integer(_), DataKind::Integer(_)) => true,
+        (DataKind::Float { .. }, DataKind::Float { .. }) => true,
+        (DataKind::Text(Some(size)), DataKind::Text(Some(limit))) => size <= limit,
+        (DataKind::Text(_), DataKind::Text(_)) => true,
+        (DataKind::Blob, DataKind::Blob) => true,
+        (DataKind::Timestamp | DataKind::Datetime, DataKind::Timestamp | DataKind::Datetime) => {
+            true
+        }
+        (DataKind::Date, DataKind::Date) => true,
+        (DataKind::Time, DataKind::Time) => true,
+        (DataKind::Year, DataKind::Year | DataKind::Integer(_)) => true,
+        (DataKind::Boolean, DataKind::Boolean | DataKind::Integer(Some(1))) => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use omi::prelude::*;
+
+    use super::{compatible, validate, Mismatch};
+    use crate as omi;
+    use crate::driver::Dialect;
+    use crate::model::DataKind;
+
+    #[derive(Debug, Default, Clone, Entity)]
+    #[entity(table = "products")]
+    struct Product {
+        #[column(primary, auto)]
+        id: u64,
+
+        #[column(size = 255)]
+        title: String,
+
+        remark: Option<String>,
+
+        enabled: bool,
+    }
+
+    #[test]
+    fn test_validate_missing_table() {
+        let mismatches = validate(&Product::meta().table, &[], Dialect::MySQL);
+
+        assert_eq!(
+            mismatches,
+            vec![Mismatch::MissingTable {
+                table: "products".into()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_validate_matching_table() {
+        let mut live = Product::meta().table;
+        live.columns[0].kind = DataKind::Integer(Some(20));
+        live.columns[3].kind = DataKind::Integer(Some(1));
+
+        assert!(validate(&Product::meta().table, &[live], Dialect::MySQL).is_empty());
+    }
+
+    #[test]
+    fn test_validate_mismatches() {
+        let mut live = Product::meta().table;
+        live.columns[1].kind = DataKind::Text(Some(100));
+        live.columns[2].null = false;
+        live.columns.remove(3);
+
+        let report: Vec<String> = validate(&Product::meta().table, &[live], Dialect::MySQL)
+            .iter()
+            .map(|m| m.to_string())
+            .collect();
+
+        assert_eq!(
+            report,
+            vec![
+                "products.title: incompatible type, expected VARCHAR(255) but found VARCHAR(100)",
+                "products.remark: the field is an Option but the column is NOT NULL",
+                "products.enabled: column does not exist",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compatible_numbers() {
+        let float = DataKind::Float {
+            max_digits: None,
+            decimal_places: None,
+        };
+
+        assert!(!compatible(&DataKind::Integer(None), &float));
+        assert!(!compatible(&float, &DataKind::Integer(None)));
+        assert!(compatible(&float, &float));
+    }
+
+    #[test]
+    fn test_compatible_integer_size() {
+        assert!(compatible(
+            &DataKind::Integer(Some(11)),
+            &DataKind::Integer(Some(20))
+        ));
+        assert!(compatible(
+            &DataKind::Integer(None),
+            &DataKind::Integer(Some(20))
+        ));
+        assert!(compatible(
+            &DataKind::Integer(Some(11)),
+            &DataKind::Integer(None)
+        ));
+        assert!(!compatible(
+            &DataKind::Integer(Some(20)),
+            &DataKind::Integer(Some(11))
+        ));
+    }
+
+    #[test]
+    fn test_compatible_boolean() {
+        assert!(compatible(&DataKind::Boolean, &DataKind::Boolean));
+        assert!(compatible(&DataKind::Boolean, &DataKind::Integer(Some(1))));
+        assert!(!compatible(
+            &DataKind::Boolean,
+            &DataKind::Integer(Some(20))
+        ));
+        assert!(!compatible(&DataKind::Boolean, &DataKind::Integer(None)));
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright (c) The Amphitheatre Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::model::{ToValue, Value};
+
+/// The filter conditions of a statement, which can be built from a tuple of
+/// column and value, or an array of them joined with `AND`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filters {
+    /// `column = value`, or `column IS NULL` for a null value.
+    Eq(String, Value),
+    /// `column IN (values)`.
+    In(String, Vec<Value>),
+    /// All of the conditions.
+    And(Vec<Filters>),
+    /// Any of the conditions.
+    Or(Vec<Filters>),
+    /// A raw SQL condition.
+    Raw(String),
+}
+
+impl Filters {
+    /// Render the conditions as a SQL expression.
+    pub fn to_sql(&self) -> String {
+        match self {
+            Filters::Eq(column, Value::Null) => format!("{} IS NULL", column),
+            Filters::Eq(column, value) => format!("{} = {}", column, value.to_sql()),
+            Filters::In(_, values) if values.is_empty() => String::from("1 = 0"),
+            Filters::In(column, values) => format!(
+                "{} IN ({})",
+                column,
+                values
+                    .iter()
+                    .map(Value::to_sql)
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Filters::And(filters) => Self::join(filters, " AND ", "1 = 1"),
+            Filters::Or(filters) => Self::join(filters, " OR ", "1 = 0"),
+            Filters::Raw(sql) => sql.clone(),
+        }
+    }
+
+    fn join(filters: &[Filters], separator: &str, empty: &str) -> String {
+        match filters {
+            [] => empty.to_owned(),
+            [filter] => filter.to_sql(),
+            filters => format!(
+                "({})",
+                filters
+                    .iter()
+                    .map(Filters::to_sql)
+                    .collect::<Vec<String>>()
+                    .join(separator)
+            ),
+        }
+    }
+}
+
+impl<V: ToValue> From<(&str, V)> for Filters {
+    fn from((column, value): (&str, V)) -> Self {
+        Filters::Eq(column.to_owned(), value.to_value())
+    }
+}
+
+impl<V: ToValue, const N: usize> From<[(&str, V); N]> for Filters {
+    fn from(conditions: [(&str, V); N]) -> Self {
+        Filters::And(conditions.into_iter().map(Filters::from).collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Filters;
+    use crate::model::ToValue;
+
+    #[test]
+    fn test_filters_to_sql() {
+        assert_eq!(Filters::from(("id", 123)).to_sql(), "id = 123");
+        assert_eq!(
+            Filters::from([("foo", 123), ("bar", 456)]).to_sql(),
+            "(foo = 123 AND bar = 456)"
+        );
+        assert_eq!(
+            Filters::Or(vec![
+                ("title", "abc").into(),
+                ("remark", None::<String>).into()
+            ])
+            .to_sql(),
+            "(title = 'abc' OR remark IS NULL)"
+        );
+        assert_eq!(
+            Filters::In("product_id".into(), vec![1.to_value(), 2.to_value()]).to_sql(),
+            "product_id IN (1, 2)"
+        );
+        assert_eq!(Filters::In("product_id".into(), vec![]).to_sql(), "1 = 0");
+    }
+}
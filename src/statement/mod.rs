@@ -13,19 +13,29 @@
 // limitations under the License.
 
 mod delete_statement;
+pub(crate) use self::delete_statement::DeleteMode;
 pub use self::delete_statement::DeleteStatement;
 
+mod filters;
+pub use self::filters::Filters;
+
 mod insert_statement;
-pub use self::insert_statement::InsertStatement;
+pub use self::insert_statement::{insert_related, InsertStatement};
+
+mod preload;
+pub use self::preload::*;
 
 mod raw_statement;
 pub use self::raw_statement::RawStatement;
 
 mod select_statement;
 pub use self::select_statement::SelectStatement;
+pub(crate) use self::select_statement::SoftDeleted;
 
 mod update_statement;
 pub use self::update_statement::UpdateStatement;
+use crate::model::{Entity, Value};
+use crate::{Database, ExecResult, OmiError, Result};
 
 // Declare a type named Statement, which represents a database operation statement.
 pub enum Statement<T> {
@@ -36,5 +46,136 @@ pub enum Statement<T> {
     Update(UpdateStatement<T>),
 }
 
-#[derive(Clone, Copy)]
-pub struct Filters {}
+/// The conditions that match the row of the entity, its primary key and, for
+/// optimistic locking, the version it was loaded with. An entity without a
+/// primary key, or with a null one, fails with `MissingKey` rather than
+/// matching every row.
+pub(crate) fn entity_conditions<T: Entity>(entity: &T) -> Result<Vec<Filters>> {
+    let meta = T::meta();
+    let mut conditions = vec![];
+
+    for column in meta.table.columns.iter().filter(|column| column.primary) {
+        match entity.value(&column.name) {
+            Some(value) if !value.is_null() => {
+                conditions.push(Filters::Eq(column.name.clone(), value))
+            }
+            _ => return Err(OmiError::MissingKey(T::table_name())),
+        }
+    }
+    if conditions.is_empty() {
+        return Err(OmiError::MissingKey(T::table_name()));
+    }
+
+    if let Some(version) = meta.version {
+        if let Some(value) = entity.value(&version) {
+            conditions.push(Filters::Eq(version, value));
+        }
+    }
+
+    Ok(conditions)
+}
+
+/// Execute a write, a versioned entity is stale if no row matched. With
+/// `returning`, the statement ends with `RETURNING *` and the written rows
+/// come back loaded like the rows of a query.
+pub(crate) fn execute_write<T>(
+    db: &Database,
+    sql: String,
+    returning: bool,
+    versioned: bool,
+) -> Result<(ExecResult, Vec<T>)>
+where
+    T: Entity + Default + From<T> + Clone,
+{
+    let (result, rows) = match returning {
+        true => {
+            let mut rows = db.execute::<T>(sql)?;
+            SelectStatement::hydrate(&mut rows, &[], db)?;
+            let result = ExecResult {
+                rows_affected: rows.len() as u64,
+                last_insert_id: None,
+            };
+            (result, rows)
+        }
+        false => (db.exec(sql)?, vec![]),
+    };
+
+    match versioned && result.rows_affected == 0 {
+        true => Err(OmiError::StaleEntity(T::table_name())),
+        false => Ok((result, rows)),
+    }
+}
+
+/// Execute a write on a backend without `RETURNING`. The rows matching the
+/// conditions are selected before the write, and selected again by their
+/// primary key after it, unless they were deleted.
+pub(crate) fn execute_reselect<T>(
+    db: &Database,
+    sql: String,
+    conditions: Vec<Filters>,
+    versioned: bool,
+    deleted: bool,
+) -> Result<(ExecResult, Vec<T>)>
+where
+    T: Entity + Default + From<T> + Clone,
+{
+    let rows = select_rows::<T>(conditions, db)?;
+    let (result, _) = execute_write::<T>(db, sql, false, versioned)?;
+    if deleted {
+        return Ok((result, rows));
+    }
+
+    match key_filter(&rows) {
+        Some(keys) => Ok((result, select_rows(vec![keys], db)?)),
+        None => Ok((result, vec![])),
+    }
+}
+
+/// Select the rows matching the conditions, regardless of the default scope
+/// and soft delete.
+pub(crate) fn select_rows<T>(conditions: Vec<Filters>, db: &Database) -> Result<Vec<T>>
+where
+    T: Entity + Default + From<T> + Clone,
+{
+    let mut select = SelectStatement::new();
+    select.unscoped().with_deleted();
+    for filters in conditions {
+        select.filter(filters);
+    }
+
+    select.fetch(db, &[])
+}
+
+/// The filter that matches the entities by their primary key, `None` if
+/// there are none or the entity has no primary key.
+pub(crate) fn key_filter<T: Entity>(entities: &[T]) -> Option<Filters> {
+    let table = T::meta().table;
+    let primary = table.columns.into_iter().find(|column| column.primary)?;
+    let keys: Vec<Value> = entities
+        .iter()
+        .filter_map(|entity| entity.value(&primary.name))
+        .collect();
+
+    match keys.is_empty() {
+        true => None,
+        false => Some(Filters::In(primary.name, keys)),
+    }
+}
+
+/// Run the write in a transaction, or a savepoint inside one, if it takes
+/// more than one statement, e.g. with the callbacks or cascades of the entity.
+pub(crate) fn atomically<R>(
+    db: &Database,
+    enabled: bool,
+    write: impl FnOnce(&Database) -> Result<R>,
+) -> Result<R> {
+    if !enabled {
+        return write(db);
+    }
+
+    let tx = db.begin()?;
+    let result = write(&tx)?;
+    tx.commit()?;
+
+    Ok(result)
+}
@@ -0,0 +1,288 @@
+// Copyright (c) The Amphitheatre Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers of the eager loading code generated by `#[derive(Entity)]`, each
+//! relation is loaded with one `IN (...)` query for all parent entities.
+
+use std::collections::HashMap;
+
+use super::{Filters, SelectStatement};
+use crate::model::{Entity, JoinTable, ToValue, Value};
+use crate::{Database, Result};
+
+/// A row of a join table, the keys are selected with these aliases.
+#[derive(Debug, Default, Clone)]
+pub struct JoinRow {
+    pub source_key: Option<Value>,
+    pub target_key: Option<Value>,
+}
+
+/// The paths below the relation, e.g. `author` for `reviews.author`, or
+/// `None` if the relation is not in the paths.
+pub fn nested_paths(paths: &[String], name: &str) -> Option<Vec<String>> {
+    let mut found = false;
+    let mut nested = vec![];
+
+    for path in paths {
+        match path.split_once('.') {
+            Some((head, rest)) if head == name => {
+                found = true;
+                nested.push(rest.to_owned());
+            }
+            None if path == name => found = true,
+            _ => {}
+        }
+    }
+
+    found.then_some(nested)
+}
+
+/// The distinct, non null values of the column of the entities.
+pub fn collect_keys<T: Entity>(entities: &[T], column: &str) -> Vec<Value> {
+    let mut keys: Vec<Value> = vec![];
+
+    for key in entities.iter().filter_map(|entity| entity.value(column)) {
+        if !key.is_null() && !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+
+    keys
+}
+
+/// Whether the type column of the entity holds the type of a polymorphic
+/// relation.
+pub fn is_morph_type<T: Entity>(entity: &T, type_column: &str, type_value: &str) -> bool {
+    entity.value(type_column) == Some(Value::Text(type_value.to_owned()))
+}
+
+/// The distinct, non null values of the column of the entities whose type
+/// column holds the type.
+pub fn collect_morph_keys<T: Entity>(
+    entities: &[T],
+    type_column: &str,
+    type_value: &str,
+    column: &str,
+) -> Vec<Value> {
+    let mut keys: Vec<Value> = vec![];
+
+    for entity in entities
+        .iter()
+        .filter(|entity| is_morph_type(*entity, type_column, type_value))
+    {
+        match entity.value(column) {
+            Some(key) if !key.is_null() && !keys.contains(&key) => keys.push(key),
+            _ => {}
+        }
+    }
+
+    keys
+}
+
+/// Load the polymorphic children of the keys, whose type column holds the
+/// table name of the parent, with a single query.
+pub fn load_morphed<T>(
+    type_column: &str,
+    type_value: &str,
+    column: &str,
+    keys: Vec<Value>,
+    paths: &[String],
+    db: &Database,
+) -> Result<Vec<T>>
+where
+    T: Entity + Default + From<T> + Clone,
+{
+    if keys.is_empty() {
+        return Ok(vec![]);
+    }
+
+    SelectStatement::<T>::new()
+        .filter(Filters::Eq(type_column.to_owned(), type_value.to_value()))
+        .filter(Filters::In(column.to_owned(), keys))
+        .fetch(db, paths)
+}
+
+/// Load the entities whose column matches one of the keys with a single
+/// query, and the relations of the nested paths onto them.
+pub fn load_related<T>(
+    column: &str,
+    keys: Vec<Value>,
+    paths: &[String],
+    db: &Database,
+) -> Result<Vec<T>>
+where
+    T: Entity + Default + From<T> + Clone,
+{
+    if keys.is_empty() {
+        return Ok(vec![]);
+    }
+
+    SelectStatement::<T>::new()
+        .filter(Filters::In(column.to_owned(), keys))
+        .fetch(db, paths)
+}
+
+/// Load the entities related to the keys through the join table, with one
+/// query for the join rows and one for the entities, grouped by the key.
+pub fn load_through<T>(
+    join: &JoinTable,
+    column: &str,
+    keys: Vec<Value>,
+    paths: &[String],
+    db: &Database,
+) -> Result<HashMap<String, Vec<T>>>
+where
+    T: Entity + Default + From<T> + Clone,
+{
+    if keys.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let sql = format!(
+        "SELECT {} AS source_key, {} AS target_key FROM {} WHERE {}",
+        join.local_key,
+        join.foreign_key,
+        join.table,
+        Filters::In(join.local_key.clone(), keys).to_sql()
+    );
+    let rows = db.execute::<JoinRow>(sql)?;
+
+    let mut target_keys: Vec<Value> = vec![];
+    for key in rows.iter().filter_map(|row| row.target_key.clone()) {
+        if !target_keys.contains(&key) {
+            target_keys.push(key);
+        }
+    }
+    let targets = group_by_key(load_related::<T>(column, target_keys, paths, db)?, column);
+
+    let mut groups: HashMap<String, Vec<T>> = HashMap::new();
+    for row in rows {
+        if let (Some(source), Some(target)) = (row.source_key, row.target_key) {
+            let related = targets.get(&target.to_sql()).cloned().unwrap_or_default();
+            groups.entry(source.to_sql()).or_default().extend(related);
+        }
+    }
+
+    Ok(groups)
+}
+
+/// Group the entities by the SQL literal of the column value, so that keys
+/// of different integer types still match.
+pub fn group_by_key<T: Entity>(entities: Vec<T>, column: &str) -> HashMap<String, Vec<T>> {
+    let mut groups: HashMap<String, Vec<T>> = HashMap::new();
+
+    for entity in entities {
+        match entity.value(column) {
+            Some(key) if !key.is_null() => groups.entry(key.to_sql()).or_default().push(entity),
+            _ => {}
+        }
+    }
+
+    groups
+}
+
+#[cfg(test)]
+mod test {
+    use omi::prelude::*;
+
+    use super::{collect_keys, collect_morph_keys, group_by_key, is_morph_type, nested_paths};
+    use crate as omi;
+    use crate::model::ToValue;
+
+    #[derive(Debug, Default, Clone, PartialEq, Entity)]
+    #[entity(table = "reviews")]
+    struct Review {
+        #[column(primary, auto)]
+        id: u64,
+
+        product_id: Option<i64>,
+    }
+
+    #[derive(Debug, Default, Clone, PartialEq, Entity)]
+    #[entity(table = "comments")]
+    struct Comment {
+        #[column(primary, auto)]
+        id: u64,
+
+        commentable_type: String,
+        commentable_id: u64,
+    }
+
+    fn review(id: u64, product_id: Option<i64>) -> Review {
+        Review { id, product_id }
+    }
+
+    fn comment(id: u64, commentable_type: &str, commentable_id: u64) -> Comment {
+        Comment {
+            id,
+            commentable_type: commentable_type.to_owned(),
+            commentable_id,
+        }
+    }
+
+    #[test]
+    fn test_nested_paths() {
+        let paths = vec![
+            "reviews".to_owned(),
+            "reviews.author".to_owned(),
+            "reviews.author.avatar".to_owned(),
+            "brand".to_owned(),
+        ];
+
+        assert_eq!(
+            nested_paths(&paths, "reviews"),
+            Some(vec!["author".to_owned(), "author.avatar".to_owned()])
+        );
+        assert_eq!(nested_paths(&paths, "brand"), Some(vec![]));
+        assert_eq!(nested_paths(&paths, "tags"), None);
+    }
+
+    #[test]
+    fn test_collect_keys() {
+        let reviews = vec![review(1, Some(7)), review(2, Some(7)), review(3, None)];
+        let keys = collect_keys(&reviews, "product_id");
+
+        assert_eq!(keys, vec![Some(7i64).to_value()]);
+    }
+
+    #[test]
+    fn test_collect_morph_keys() {
+        let comments = vec![
+            comment(1, "products", 7),
+            comment(2, "articles", 7),
+            comment(3, "products", 8),
+            comment(4, "products", 7),
+        ];
+
+        assert!(is_morph_type(&comments[0], "commentable_type", "products"));
+        assert!(!is_morph_type(&comments[1], "commentable_type", "products"));
+        assert_eq!(
+            collect_morph_keys(&comments, "commentable_type", "products", "commentable_id"),
+            vec![7u64.to_value(), 8u64.to_value()]
+        );
+        assert_eq!(
+            collect_morph_keys(&comments, "commentable_type", "articles", "commentable_id"),
+            vec![7u64.to_value()]
+        );
+    }
+
+    #[test]
+    fn test_group_by_key() {
+        let reviews = vec![review(1, Some(7)), review(2, Some(8)), review(3, Some(7))];
+        let groups = group_by_key(reviews, "product_id");
+
+        assert_eq!(groups["7"], vec![review(1, Some(7)), review(3, Some(7))]);
+        assert_eq!(groups["8"], vec![review(2, Some(8))]);
+    }
+}
@@ -17,7 +17,7 @@ use std::marker::PhantomData;
 use super::Statement;
 use crate::builder::Builder;
 use crate::model::Entity;
-use crate::{Database, OmiError, Result};
+use crate::{Database, Result};
 
 // Represents a database raw operation statement.
 #[derive(Clone)]
@@ -40,11 +40,6 @@ where
     /// Implement the execute() method for the Statement type
     pub fn execute(&self, db: &Database) -> Result<Vec<T>> {
         let sql = Builder::build(Statement::Raw(self.clone()));
-        let result = db.execute::<T>(sql);
-
-        match result {
-            Ok(entities) => Ok(entities),
-            Err(_) => Err(OmiError::DatabaseError),
-        }
+        db.execute::<T>(sql)
     }
 }
@@ -12,7 +12,6 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::collections::HashMap;
 use std::marker::PhantomData;
 
 use super::{Filters, Statement};
@@ -21,6 +20,14 @@ use crate::model::Entity;
 use crate::order::Direction;
 use crate::{Database, OmiError, Result};
 
+/// Which rows of an entity with soft delete the statement selects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SoftDeleted {
+    Excluded,
+    Included,
+    Only,
+}
+
 // Represents a database SELECT operation statement.
 #[derive(Clone)]
 pub struct SelectStatement<T> {
@@ -36,13 +43,19 @@ pub struct SelectStatement<T> {
 
     /// Used to store grouping fields
     pub(crate) groups: Vec<String>,
-    /// Used to store sorting fields and direction
-    pub(crate) orders: HashMap<String, Direction>,
+    /// Used to store sorting fields and direction, in order of priority
+    pub(crate) orders: Vec<(String, Direction)>,
 
     /// Used to store the offset value
     pub(crate) offset: Option<i64>,
     /// Used to store the limit value
     pub(crate) limit: Option<i64>,
+
+    /// Used to store which soft deleted rows are selected
+    pub(crate) deleted: SoftDeleted,
+
+    /// Whether the default scope of the entity is applied
+    pub(crate) scoped: bool,
 }
 
 impl<T> SelectStatement<T>
@@ -56,28 +69,30 @@ where
             groups: vec![],
             includes: vec![],
             excludes: vec![],
-            orders: HashMap::new(),
+            orders: vec![],
             offset: None,
             limit: None,
+            deleted: SoftDeleted::Excluded,
+            scoped: true,
         }
     }
 
     /// Implement the filter() method for the Statement type
-    pub fn filter(&mut self, filters: Filters) -> &mut Self {
+    pub fn filter(&mut self, filters: impl Into<Filters>) -> &mut Self {
         // Add the filter conditions to the filters attribute
-        self.filters.push(filters);
+        self.filters.push(filters.into());
         self
     }
 
     /// Include associated objects
-    pub fn include(&mut self, cols: impl Into<Vec<String>>) -> &mut Self {
-        self.includes = cols.into();
+    pub fn include<S: Into<String>>(&mut self, paths: impl IntoIterator<Item = S>) -> &mut Self {
+        self.includes = paths.into_iter().map(Into::into).collect();
         self
     }
 
     /// Exclude associated objects
-    pub fn exclude(&mut self, cols: impl Into<Vec<String>>) -> &mut Self {
-        self.excludes = cols.into();
+    pub fn exclude<S: Into<String>>(&mut self, paths: impl IntoIterator<Item = S>) -> &mut Self {
+        self.excludes = paths.into_iter().map(Into::into).collect();
         self
     }
 
@@ -87,8 +102,8 @@ where
         self
     }
 
-    /// Add the sorting field to the orders attribute
-    pub fn order_by(&mut self, cols: impl Into<HashMap<String, Direction>>) -> &mut Self {
+    /// Add the sorting fields to the orders attribute, the first one sorts first
+    pub fn order_by(&mut self, cols: impl Into<Vec<(String, Direction)>>) -> &mut Self {
         self.orders = cols.into();
         self
     }
@@ -107,34 +122,115 @@ where
         self
     }
 
-    /// Retrieve a single record
-    pub fn one(&mut self, db: &Database) -> Result<T> {
-        self.limit = Some(1);
+    /// Bypass the default scope of the entity.
+    pub fn unscoped(&mut self) -> &mut Self {
+        self.scoped = false;
+        self
+    }
 
-        let sql = Builder::build(Statement::Select(self.clone()));
-        let result = db.execute::<T>(sql);
-
-        match result {
-            Ok(entities) => match entities.first() {
-                Some(entity) => Ok(entity.clone()),
-                None => Err(OmiError::NotFoundError),
-            },
-            Err(_) => Err(OmiError::DatabaseError),
-        }
+    /// Select the soft deleted rows as well.
+    pub fn with_deleted(&mut self) -> &mut Self {
+        self.deleted = SoftDeleted::Included;
+        self
+    }
+
+    /// Select the soft deleted rows only.
+    pub fn only_deleted(&mut self) -> &mut Self {
+        self.deleted = SoftDeleted::Only;
+        self
+    }
+
+    /// Retrieve a single record, fails with `NotFoundError` if there's none
+    pub fn one(&self, db: &Database) -> Result<T> {
+        self.first(db)?.ok_or(OmiError::NotFoundError)
+    }
+
+    /// Retrieve the first record, `None` if there's none
+    pub fn first(&self, db: &Database) -> Result<Option<T>> {
+        let statement = self.limited(1);
+
+        Ok(statement
+            .fetch(db, &self.relation_paths())?
+            .into_iter()
+            .next())
+    }
+
+    /// Retrieve the only record, `None` if there's none, fails with
+    /// `MultipleRows` if there's more than one
+    pub fn one_or_none(&self, db: &Database) -> Result<Option<T>> {
+        let statement = self.limited(2);
+
+        only(statement.fetch(db, &self.relation_paths())?)
+    }
+
+    /// Retrieve the only record, fails with `NotFoundError` if there's none
+    /// and `MultipleRows` if there's more than one
+    pub fn exactly_one(&self, db: &Database) -> Result<T> {
+        self.one_or_none(db)?.ok_or(OmiError::NotFoundError)
     }
 
-    /// Fetch multiple rows
+    /// Fetch multiple rows, an empty list if none matches
     pub fn all(&self, db: &Database) -> Result<Vec<T>> {
+        self.fetch(db, &self.relation_paths())
+    }
+
+    /// A copy of the statement selecting at most `limit` rows, or fewer if
+    /// the statement is limited to fewer already.
+    pub(crate) fn limited(&self, limit: i64) -> Self {
+        Self {
+            limit: Some(self.limit.map_or(limit, |l| l.min(limit))),
+            ..self.clone()
+        }
+    }
+
+    /// The relation paths to load, all direct relations of the entity but the
+    /// lazy ones unless some are included, without the excluded paths and
+    /// those below them.
+    pub(crate) fn relation_paths(&self) -> Vec<String> {
+        let paths = match self.includes.is_empty() {
+            true => T::meta()
+                .relations
+                .into_iter()
+                .filter(|r| !r.lazy)
+                .map(|r| r.name)
+                .collect(),
+            false => self.includes.clone(),
+        };
+
+        paths
+            .into_iter()
+            .filter(|path| {
+                !self
+                    .excludes
+                    .iter()
+                    .any(|exclude| path == exclude || path.starts_with(&format!("{}.", exclude)))
+            })
+            .collect()
+    }
+
+    /// Run the query and load the relations of the paths onto the entities.
+    pub(crate) fn fetch(&self, db: &Database, paths: &[String]) -> Result<Vec<T>> {
         let sql = Builder::build(Statement::Select(self.clone()));
-        let result = db.execute::<T>(sql);
-
-        match result {
-            Ok(entities) => match !entities.is_empty() {
-                true => Ok(entities),
-                false => Err(OmiError::NotFoundError),
-            },
-            Err(_) => Err(OmiError::DatabaseError),
+        let mut entities = db.execute::<T>(sql)?;
+        Self::hydrate(&mut entities, paths, db)?;
+
+        Ok(entities)
+    }
+
+    /// Take the snapshots of the loaded entities, run their `after_load`
+    /// callbacks and load the relations of the paths onto them.
+    pub(crate) fn hydrate(entities: &mut [T], paths: &[String], db: &Database) -> Result<()> {
+        for entity in entities.iter_mut() {
+            entity.take_snapshot();
+            entity.after_load(db)?;
+        }
+
+        // Called without paths as well, to set up the lazy relations.
+        if !entities.is_empty() {
+            T::load_relations(entities, paths, db)?;
         }
+
+        Ok(())
     }
 }
 
@@ -147,19 +243,57 @@ where
     }
 }
 
+/// The only entity of the list, fails if there's more than one.
+fn only<T: Entity>(entities: Vec<T>) -> Result<Option<T>> {
+    let mut entities = entities.into_iter();
+
+    match (entities.next(), entities.next()) {
+        (_, Some(_)) => Err(OmiError::MultipleRows(T::table_name())),
+        (entity, None) => Ok(entity),
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use std::collections::HashMap;
-
     use omi::prelude::*;
 
-    use super::SelectStatement;
+    use super::{only, SelectStatement};
     use crate as omi;
-    use crate::order::Direction::Desc;
+    use crate::builder::Builder;
+    use crate::order::Direction::{Asc, Desc};
+    use crate::statement::{Filters, Statement};
+    use crate::OmiError;
 
     #[derive(Debug, Default, Clone, Entity)]
     #[entity(table = "products")]
-    struct Product {}
+    struct Product {
+        #[column(primary, auto)]
+        id: u64,
+
+        brand_id: u64,
+
+        #[relation(belongs_to)]
+        brand: Option<Brand>,
+
+        #[relation(has_many)]
+        reviews: Vec<Review>,
+    }
+
+    #[derive(Debug, Default, Clone, Entity)]
+    #[entity(table = "brands")]
+    struct Brand {
+        #[column(primary, auto)]
+        id: u64,
+    }
+
+    #[derive(Debug, Default, Clone, Entity)]
+    #[entity(table = "reviews")]
+    struct Review {
+        #[column(primary, auto)]
+        id: u64,
+
+        product_id: u64,
+    }
 
     #[test]
     fn test_group_by() {
@@ -172,8 +306,118 @@ mod test {
     #[test]
     fn test_order_by() {
         let mut stmt: SelectStatement<Product> = SelectStatement::new();
-        stmt.order_by([("id".into(), Desc)]);
+        stmt.order_by([("title".into(), Asc), ("id".into(), Desc)]);
+
+        assert_eq!(
+            stmt.orders,
+            vec![("title".into(), Asc), ("id".into(), Desc)]
+        );
+    }
+
+    #[derive(Debug, Default, Clone, Entity)]
+    #[entity(table = "comments", soft_delete = "deleted_at")]
+    struct Comment {
+        #[column(primary, auto)]
+        id: u64,
+
+        deleted_at: Option<String>,
+    }
+
+    #[test]
+    fn test_soft_deleted() {
+        let build =
+            |stmt: &SelectStatement<Comment>| Builder::build(Statement::Select(stmt.clone()));
+        let mut stmt: SelectStatement<Comment> = SelectStatement::new();
+        assert_eq!(
+            build(&stmt),
+            "SELECT * FROM comments WHERE deleted_at IS NULL"
+        );
+
+        stmt.with_deleted();
+        assert_eq!(build(&stmt), "SELECT * FROM comments");
+
+        stmt.only_deleted();
+        assert_eq!(
+            build(&stmt),
+            "SELECT * FROM comments WHERE deleted_at IS NOT NULL"
+        );
+    }
+
+    #[derive(Debug, Default, Clone, Entity)]
+    #[entity(
+        table = "pages",
+        default_scope = "tenant_scope",
+        scope(name = "published"),
+        scope(name = "featured", filter = "featured_filter")
+    )]
+    struct Page {
+        #[column(primary, auto)]
+        id: u64,
+
+        tenant_id: u64,
+    }
+
+    impl Page {
+        fn published() -> Filters {
+            Filters::Raw("published_at IS NOT NULL".into())
+        }
+    }
+
+    fn tenant_scope() -> (&'static str, u64) {
+        ("tenant_id", 1)
+    }
+
+    fn featured_filter() -> (&'static str, bool) {
+        ("featured", true)
+    }
+
+    #[test]
+    fn test_scopes() {
+        let build = |stmt: &SelectStatement<Page>| Builder::build(Statement::Select(stmt.clone()));
+        let mut stmt: SelectStatement<Page> = SelectStatement::new();
+        assert_eq!(build(&stmt), "SELECT * FROM pages WHERE tenant_id = 1");
+
+        stmt.published().featured();
+        assert_eq!(
+            build(&stmt),
+            "SELECT * FROM pages WHERE published_at IS NOT NULL AND featured = TRUE AND tenant_id = 1"
+        );
+
+        stmt.unscoped();
+        assert_eq!(
+            build(&stmt),
+            "SELECT * FROM pages WHERE published_at IS NOT NULL AND featured = TRUE"
+        );
+    }
+
+    #[test]
+    fn test_relation_paths() {
+        let mut stmt: SelectStatement<Product> = SelectStatement::new();
+        assert_eq!(stmt.relation_paths(), vec!["brand", "reviews"]);
+
+        stmt.exclude(["brand"]);
+        assert_eq!(stmt.relation_paths(), vec!["reviews"]);
+
+        stmt.include(["reviews", "reviews.author"]);
+        stmt.exclude(["reviews.author"]);
+        assert_eq!(stmt.relation_paths(), vec!["reviews"]);
+    }
+
+    #[test]
+    fn test_only() {
+        let product = |id| Product {
+            id,
+            ..Default::default()
+        };
 
-        assert_eq!(stmt.orders, HashMap::from([("id".into(), Desc)]));
+        assert!(matches!(only::<Product>(vec![]), Ok(None)));
+        assert!(matches!(
+            only(vec![product(1)]),
+            Ok(Some(Product { id: 1, .. }))
+        ));
+        assert_eq!(
+            only(vec![product(1), product(2)]).unwrap_err(),
+            OmiError::MultipleRows(String::from("products"))
+        );
     }
 }
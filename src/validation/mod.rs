@@ -0,0 +1,229 @@
+// Copyright (c) The Amphitheatre Authors. All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//      https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The field validators checked before an entity is inserted or updated,
+//! declared with `#[validate(...)]` on the fields of the entity.
+
+use std::fmt;
+
+pub use once_cell::sync::Lazy;
+pub use regex::Regex;
+
+use crate::model::{DataKind, Entity, Value};
+use crate::{OmiError, Result};
+
+/// The validation error of a field.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldError {
+    /// The column of the field.
+    pub field: String,
+    pub message: String,
+}
+
+/// The validation errors of an entity, in the order of its fields.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationErrors {
+    pub errors: Vec<FieldError>,
+}
+
+impl ValidationErrors {
+    /// Record the message of a failed validator for the field.
+    pub fn check(&mut self, field: &str, message: Option<String>) {
+        if let Some(message) = message {
+            if !self
+                .errors
+                .iter()
+                .any(|e| e.field == field && e.message == message)
+            {
+                self.errors.push(FieldError {
+                    field: field.to_string(),
+                    message,
+                });
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// The messages of the field.
+    pub fn field(&self, field: &str) -> Vec<&str> {
+        self.errors
+            .iter()
+            .filter(|e| e.field == field)
+            .map(|e| e.message.as_str())
+            .collect()
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let errors: Vec<String> = self
+            .errors
+            .iter()
+            .map(|e| format!("{} {}", e.field, e.message))
+            .collect();
+        write!(f, "{}", errors.join(", "))
+    }
+}
+
+/// Validate the entity, checking the declared validators and the length of
+/// the text columns with a size.
+pub fn validate<T: Entity>(entity: &T) -> Result<()> {
+    let mut errors = ValidationErrors::default();
+
+    for column in T::meta().table.columns {
+        if let (DataKind::Text(Some(size)), Some(value)) =
+            (&column.kind, entity.value(&column.name))
+        {
+            errors.check(&column.name, length(&value, None, Some(*size)));
+        }
+    }
+    entity.validate(&mut errors);
+
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(OmiError::Validation(errors)),
+    }
+}
+
+/// Check the number of characters of a text or bytes of a blob.
+pub fn length(value: &Value, min: Option<usize>, max: Option<usize>) -> Option<String> {
+    let len = match value {
+        Value::Text(text) => text.chars().count(),
+        Value::Bytes(bytes) => bytes.len(),
+        _ => return None,
+    };
+
+    match (min, max) {
+        (Some(min), _) if len < min => Some(format!("must be at least {} characters", min)),
+        (_, Some(max)) if len > max => Some(format!("must be at most {} characters", max)),
+        _ => None,
+    }
+}
+
+/// Check that the number is within the bounds.
+pub fn range(value: &Value, min: Option<f64>, max: Option<f64>) -> Option<String> {
+    let number = match value {
+        Value::Int(n) => *n as f64,
+        Value::UInt(n) => *n as f64,
+        Value::Float(n) => *n,
+        _ => return None,
+    };
+
+    match (min, max) {
+        (Some(min), _) if number < min => Some(format!("must be at least {}", min)),
+        (_, Some(max)) if number > max => Some(format!("must be at most {}", max)),
+        _ => None,
+    }
+}
+
+/// Check that the text looks like an email address, a local part and a
+/// domain with at least one dot.
+pub fn email(value: &Value) -> Option<String> {
+    let text = match value {
+        Value::Text(text) => text,
+        _ => return None,
+    };
+
+    let valid = match text.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.contains('@')
+                && !text.chars().any(char::is_whitespace)
+                && domain.contains('.')
+                && domain.split('.').all(|label| !label.is_empty())
+        }
+        None => false,
+    };
+
+    (!valid).then(|| String::from("must be a valid email address"))
+}
+
+/// Check that the text matches the pattern.
+pub fn regex(value: &Value, pattern: &Regex) -> Option<String> {
+    let text = match value {
+        Value::Text(text) => text,
+        _ => return None,
+    };
+
+    (!pattern.is_match(text)).then(|| format!("must match `{}`", pattern.as_str()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_length() {
+        let value = Value::Text(String::from("héllo"));
+
+        assert_eq!(length(&value, Some(1), Some(5)), None);
+        assert_eq!(
+            length(&value, None, Some(4)),
+            Some(String::from("must be at most 4 characters"))
+        );
+        assert_eq!(
+            length(&value, Some(6), None),
+            Some(String::from("must be at least 6 characters"))
+        );
+        assert_eq!(length(&Value::Null, Some(1), None), None);
+    }
+
+    #[test]
+    fn test_range() {
+        assert_eq!(range(&Value::Int(5), Some(0.0), Some(10.0)), None);
+        assert_eq!(
+            range(&Value::Int(-1), Some(0.0), None),
+            Some(String::from("must be at least 0"))
+        );
+        assert_eq!(
+            range(&Value::Float(10.5), None, Some(10.0)),
+            Some(String::from("must be at most 10"))
+        );
+    }
+
+    #[test]
+    fn test_email() {
+        assert_eq!(email(&Value::Text(String::from("jane@example.com"))), None);
+        assert!(email(&Value::Text(String::from("jane@example"))).is_some());
+        assert!(email(&Value::Text(String::from("jane doe@example.com"))).is_some());
+        assert!(email(&Value::Text(String::from("@example.com"))).is_some());
+        assert_eq!(email(&Value::Null), None);
+    }
+
+    #[test]
+    fn test_regex() {
+        let pattern = Regex::new("^[a-z-]+$").unwrap();
+
+        assert_eq!(regex(&Value::Text(String::from("my-post")), &pattern), None);
+        assert_eq!(
+            regex(&Value::Text(String::from("My Post")), &pattern),
+            Some(String::from("must match `^[a-z-]+$`"))
+        );
+        assert_eq!(regex(&Value::Null, &pattern), None);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut errors = ValidationErrors::default();
+        errors.check("title", Some(String::from("must be at most 5 characters")));
+        errors.check("title", Some(String::from("must be at most 5 characters")));
+        errors.check("email", None);
+
+        assert_eq!(errors.field("title"), vec!["must be at most 5 characters"]);
+        assert_eq!(errors.to_string(), "title must be at most 5 characters");
+    }
+}